@@ -1,662 +1,581 @@
-use std::process::Command;
 use std::fs;
 use std::path::PathBuf;
-use crate::models::print_config::{PrinterInfo, PrintRequest, PrintRequestPages, PrintResult, PrintConfig, Orientation};
-use headless_chrome::Browser;
-use base64::{Engine as _, engine::general_purpose::STANDARD};
+use std::sync::Arc;
+use crate::models::document::{ConformanceReport, Document, OverflowReport, PagePreview, PaginatedDocument, SavedPdf};
+use crate::models::pdf_options::PdfExportOptions;
+use crate::models::print_config::{
+    PaginateRequest, PreviewRequest, PrinterInfo, PrintRequest, PrintRequestPages, PrintResult,
+};
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobOptions, JobStatus, PrintJob};
+use crate::models::print_progress::{PrintStage, PRINT_PROGRESS_EVENT};
+use crate::models::printer_capabilities::PrinterCapabilities;
+use crate::models::receipt::ReceiptRequest;
+use crate::services::browser_pool::BrowserPool;
+use crate::services::document_renderer::DocumentRenderer;
+use crate::services::page_preview::render_previews;
+use crate::services::paginator::paginate;
+use crate::services::print_backend::{PrintBackend, SharedBackend};
+use crate::services::print_task::{PrintTask, PrintTasks, ProgressSink, RunningTask};
+use crate::services::print_workers::PrintWorkers;
+use crate::services::receipt::{render_receipt, send_receipt};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Chrome, lpstat/PowerShell and IPP calls all block, so every command below runs them
+// off the async runtime: renders and print jobs on the bounded `PrintWorkers` pool,
+// quick backend queries on Tauri's blocking thread pool.
+
+/// Lists all available printers of the configured backend
+#[tauri::command]
+pub async fn get_printers(backend: State<'_, SharedBackend>) -> Result<Vec<PrinterInfo>, PrintError> {
+    let backend = backend.inner().clone();
+    blocking(move || backend.list_printers()).await
+}
 
-/// Lists all available printers on the system
+/// Reports the media, duplex, color, resolution, tray and margin support of a printer
 #[tauri::command]
-pub async fn get_printers() -> Result<Vec<PrinterInfo>, String> {
-    #[cfg(target_os = "linux")]
-    {
-        get_printers_linux().await
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        get_printers_windows().await
-    }
-    
-    #[cfg(target_os = "macos")]
-    {
-        get_printers_macos().await
-    }
+pub async fn get_printer_capabilities(backend: State<'_, SharedBackend>, printer_id: String) -> Result<PrinterCapabilities, PrintError> {
+    let backend = backend.inner().clone();
+    blocking(move || backend.capabilities(&printer_id)).await
 }
 
-#[cfg(target_os = "linux")]
-async fn get_printers_linux() -> Result<Vec<PrinterInfo>, String> {
-    // Execute lpstat -p -d to list printers
-    let output = Command::new("lpstat")
-        .args(&["-p", "-d"])
-        .output()
-        .map_err(|e| format!("Error executing lpstat: {}. Check if CUPS is installed.", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "lpstat failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+/// Renders the document to a temporary PDF file using Chrome headless
+fn render_to_temp_pdf(document: &Document, renderer: &DocumentRenderer) -> Result<(PathBuf, Option<ConformanceReport>), PrintError> {
+    let temp_dir = std::env::temp_dir();
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let pdf_path = temp_dir.join(format!("print-{}.pdf", job_id));
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut printers = Vec::new();
-    let mut default_printer: Option<String> = None;
-
-    for line in stdout.lines() {
-        if line.starts_with("printer ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let printer_id = parts[1].to_string();
-                printers.push(PrinterInfo {
-                    id: printer_id.clone(),
-                    display_name: printer_id.replace('_', " "),
-                    is_default: false,
-                });
-            }
-        } else if line.contains("default destination:") {
-            if let Some(name) = line.split(':').nth(1) {
-                default_printer = Some(name.trim().to_string());
-            }
-        }
-    }
+    let rendered = renderer.render_pdf(document)?;
 
-    if let Some(default) = default_printer {
-        for printer in &mut printers {
-            if printer.id == default {
-                printer.is_default = true;
-                break;
-            }
-        }
-    }
+    fs::write(&pdf_path, &rendered.data)
+        .map_err(|e| PrintError::io(&pdf_path, e))?;
 
-    if printers.iter().all(|p| !p.is_default) && !printers.is_empty() {
-        printers[0].is_default = true;
-    }
+    Ok((pdf_path, rendered.conformance))
+}
 
-    Ok(printers)
+/// Reports which pages have content cut off by the fixed page box
+#[tauri::command]
+pub async fn check_page_overflow(app: AppHandle, workers: State<'_, PrintWorkers>, request: PrintRequestPages) -> Result<OverflowReport, PrintError> {
+    workers.run(move || {
+        let pool = app.state::<BrowserPool>();
+        DocumentRenderer::new(&pool).check_overflow(&Document::from(&request))
+    })
+    .await
 }
 
-#[cfg(target_os = "windows")]
-async fn get_printers_windows() -> Result<Vec<PrinterInfo>, String> {
-    // Use PowerShell to list printers
-    let output = Command::new("powershell")
-        .args(&[
-            "-Command",
-            "Get-Printer | Select-Object Name, Default | ConvertTo-Json"
-        ])
-        .output()
-        .map_err(|e| format!("Error executing PowerShell: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "PowerShell failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+/// Splits one long HTML document into pages matching the PDF layout
+#[tauri::command]
+pub async fn paginate_document(app: AppHandle, workers: State<'_, PrintWorkers>, request: PaginateRequest) -> Result<PaginatedDocument, PrintError> {
+    workers.run(move || {
+        let pool = app.state::<BrowserPool>();
+        paginate(&DocumentRenderer::new(&pool), &request.config, &request.html_content)
+    })
+    .await
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    
-    // Parse JSON (simplified - use serde_json in production)
-    let mut printers = Vec::new();
-    
-    // Simple fallback if JSON parsing fails
-    if stdout.contains("Name") {
-        // Try to extract printer names from output
-        for line in stdout.lines() {
-            if line.contains("\"Name\"") {
-                if let Some(name_part) = line.split(':').nth(1) {
-                    let name = name_part
-                        .trim()
-                        .trim_matches(',')
-                        .trim_matches('"')
-                        .to_string();
-                    if !name.is_empty() {
-                        printers.push(PrinterInfo {
-                            id: name.clone(),
-                            display_name: name,
-                            is_default: false,
-                        });
-                    }
-                }
-            }
-        }
-    }
+/// Renders pages to PNG thumbnails with the same layout as the PDF
+#[tauri::command]
+pub async fn render_page_previews(app: AppHandle, workers: State<'_, PrintWorkers>, request: PreviewRequest) -> Result<Vec<PagePreview>, PrintError> {
+    workers.run(move || {
+        let pool = app.state::<BrowserPool>();
+        let document = Document::new(request.pages, request.config);
+        render_previews(&DocumentRenderer::new(&pool), &document, request.dpi, request.page_index)
+    })
+    .await
+}
 
-    // Mark first as default if none was marked
-    if !printers.is_empty() {
-        printers[0].is_default = true;
-    }
+/// Saves document as PDF directly to specified path, encrypted, signed or as PDF/A when asked
+#[tauri::command]
+pub async fn save_pdf_to_path(
+    app: AppHandle,
+    workers: State<'_, PrintWorkers>,
+    tasks: State<'_, PrintTasks>,
+    request: PrintRequest,
+    destination_path: String,
+    export_options: Option<PdfExportOptions>,
+) -> Result<SavedPdf, PrintError> {
+    let document = Document::from(&request).exported(export_options.unwrap_or_default())?;
+    let task = start_task(&app, &workers, &tasks, request.task_id.clone())?;
+
+    workers.run(move || save_pdf(&app, &task, &document, destination_path)).await
+}
 
-    Ok(printers)
+/// Saves document with multiple pages as PDF (merge in backend), encrypted, signed or as PDF/A when asked
+#[tauri::command]
+pub async fn save_pdf_pages_to_path(
+    app: AppHandle,
+    workers: State<'_, PrintWorkers>,
+    tasks: State<'_, PrintTasks>,
+    request: PrintRequestPages,
+    destination_path: String,
+    export_options: Option<PdfExportOptions>,
+) -> Result<SavedPdf, PrintError> {
+    let document = Document::from(&request).exported(export_options.unwrap_or_default())?;
+    let task = start_task(&app, &workers, &tasks, request.task_id.clone())?;
+
+    workers.run(move || save_pdf(&app, &task, &document, destination_path)).await
 }
 
-#[cfg(target_os = "macos")]
-async fn get_printers_macos() -> Result<Vec<PrinterInfo>, String> {
-    let output = Command::new("lpstat")
-        .args(&["-p", "-d"])
-        .output()
-        .map_err(|e| format!("Error executing lpstat: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "lpstat failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+/// Prints a single-content document through the configured backend
+#[tauri::command]
+pub async fn print_document(
+    app: AppHandle,
+    workers: State<'_, PrintWorkers>,
+    tasks: State<'_, PrintTasks>,
+    request: PrintRequest,
+) -> Result<PrintResult, PrintError> {
+    let document = Document::from(&request);
+    // Fail on bad options before queueing the slow render; page ranges are checked
+    // once pagination has counted the pages
+    request.options.validate(usize::MAX)?;
+
+    let task = start_task(&app, &workers, &tasks, request.task_id.clone())?;
+    workers
+        .run(move || print_rendered(&app, &task, &document, request.printer_id.as_deref(), request.options))
+        .await
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut printers = Vec::new();
-    let mut default_printer: Option<String> = None;
-
-    for line in stdout.lines() {
-        if line.starts_with("printer ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let printer_id = parts[1].to_string();
-                printers.push(PrinterInfo {
-                    id: printer_id.clone(),
-                    display_name: printer_id.replace('_', " "),
-                    is_default: false,
-                });
-            }
-        } else if line.contains("default destination:") {
-            if let Some(name) = line.split(':').nth(1) {
-                default_printer = Some(name.trim().to_string());
-            }
-        }
-    }
+/// Prints document with multiple pages (merge in backend)
+#[tauri::command]
+pub async fn print_document_pages(
+    app: AppHandle,
+    workers: State<'_, PrintWorkers>,
+    tasks: State<'_, PrintTasks>,
+    request: PrintRequestPages,
+) -> Result<PrintResult, PrintError> {
+    let document = Document::from(&request);
+    // Fail on bad options before queueing the slow render
+    request.options.validate(document.pages.len())?;
+
+    let task = start_task(&app, &workers, &tasks, request.task_id.clone())?;
+    workers
+        .run(move || print_rendered(&app, &task, &document, request.printer_id.as_deref(), request.options))
+        .await
+}
 
-    if let Some(default) = default_printer {
-        for printer in &mut printers {
-            if printer.id == default {
-                printer.is_default = true;
-                break;
-            }
-        }
-    }
+/// Renders a receipt for a thermal printer and sends it as ESC/POS
+///
+/// Bypasses the print backend: the job goes straight to the device file or raw socket.
+#[tauri::command]
+pub async fn print_receipt(
+    app: AppHandle,
+    workers: State<'_, PrintWorkers>,
+    tasks: State<'_, PrintTasks>,
+    request: ReceiptRequest,
+) -> Result<PrintResult, PrintError> {
+    let task = start_task(&app, &workers, &tasks, request.task_id.clone())?;
+    workers.run(move || print_rendered_receipt(&app, &task, &request)).await
+}
 
-    if printers.iter().all(|p| !p.is_default) && !printers.is_empty() {
-        printers[0].is_default = true;
-    }
+/// Reads the current state of a job submitted through the configured backend
+#[tauri::command]
+pub async fn get_job_status(backend: State<'_, SharedBackend>, printer_id: String, job_id: String) -> Result<JobStatus, PrintError> {
+    let backend = backend.inner().clone();
+    blocking(move || backend.job_status(&printer_id, &job_id)).await
+}
 
-    Ok(printers)
+/// Cancels a job that has not finished printing yet
+#[tauri::command]
+pub async fn cancel_job(backend: State<'_, SharedBackend>, printer_id: String, job_id: String) -> Result<(), PrintError> {
+    let backend = backend.inner().clone();
+    blocking(move || backend.cancel_job(&printer_id, &job_id)).await
 }
 
-/// Converts HTML to PDF using Chrome headless (no external dependencies!)
-fn html_to_pdf(html_content: &str, request: &PrintRequest) -> Result<PathBuf, String> {
-    // Create temporary directory
-    let temp_dir = std::env::temp_dir();
-    let job_id = uuid::Uuid::new_v4().to_string();
-    let pdf_path = temp_dir.join(format!("print-{}.pdf", job_id));
+/// Lists the jobs of one printer, or of every printer when `printer_id` is omitted
+#[tauri::command]
+pub async fn list_jobs(
+    backend: State<'_, SharedBackend>,
+    printer_id: Option<String>,
+    filter: Option<JobFilter>,
+) -> Result<Vec<JobStatus>, PrintError> {
+    let backend = backend.inner().clone();
+    blocking(move || backend.list_jobs(printer_id.as_deref(), filter.unwrap_or_default())).await
+}
 
-    // Prepare full HTML with inline CSS
-    let full_html = prepare_full_html(html_content, request);
+/// Stops a running or queued print or save call before its next stage
+///
+/// Rendering stops after the batch of pages being printed. Once the job reached the
+/// printer, use `cancel_job` with its job id instead.
+#[tauri::command]
+pub async fn cancel_print(tasks: State<'_, PrintTasks>, task_id: String) -> Result<(), PrintError> {
+    tasks.cancel(&task_id)
+}
 
-    // Start Chrome headless
-    let browser = Browser::default()
-        .map_err(|e| format!("Error starting Chrome headless: {}. Is Chrome/Chromium installed?", e))?;
+/// Runs a short blocking backend call on Tauri's blocking thread pool
+async fn blocking<T, F>(call: F) -> Result<T, PrintError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, PrintError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(call)
+        .await
+        .map_err(|e| PrintError::spooler("blocking task", e))?
+}
 
-    let tab = browser.new_tab()
-        .map_err(|e| format!("Error creating new tab: {}", e))?;
+/// Registers the task before it is queued, so it can be canceled while waiting
+fn start_task(app: &AppHandle, workers: &PrintWorkers, tasks: &PrintTasks, task_id: Option<String>) -> Result<RunningTask, PrintError> {
+    let task = tasks.start(task_id, progress_sink(app.clone()))?;
+    if workers.is_busy() {
+        task.report(PrintStage::Queued);
+    }
+    Ok(task)
+}
 
-    // Convert HTML to data URI to avoid file:// issues on Windows
-    let html_base64 = STANDARD.encode(full_html.as_bytes());
-    let data_uri = format!("data:text/html;base64,{}", html_base64);
+/// Renders the document and copies it to `destination_path` (runs on a print worker)
+fn save_pdf(app: &AppHandle, task: &PrintTask, document: &Document, destination_path: String) -> Result<SavedPdf, PrintError> {
+    // 1. Create temporary PDF
+    let pool = app.state::<BrowserPool>();
+    let renderer = DocumentRenderer::new(&pool).with_task(task);
+    let (temp_pdf_path, conformance) = render_to_temp_pdf(document, &renderer)?;
 
-    // Navigate to HTML
-    tab.navigate_to(&data_uri)
-        .map_err(|e| format!("Error loading HTML: {}", e))?;
+    // 2. Move temporary file to destination, unless canceled meanwhile
+    let copied = task.advance(PrintStage::WritingPdf).and_then(|_| {
+        fs::copy(&temp_pdf_path, &destination_path).map_err(|e| PrintError::io(&destination_path, e))
+    });
 
-    // Wait for complete loading
-    tab.wait_until_navigated()
-        .map_err(|e| format!("Error waiting for navigation: {}", e))?;
+    // 3. Remove temporary
+    let _ = fs::remove_file(&temp_pdf_path);
+    copied?;
 
-    // Wait a bit more to ensure CSS/fonts are loaded
-    std::thread::sleep(std::time::Duration::from_millis(500));
+    // 4. Return final path, with the PDF/A report if any
+    Ok(SavedPdf { path: destination_path, conformance })
+}
 
-    // Configure PDF options
-    let (width, height) = request.config.format.dimensions_mm();
-    let (page_width, page_height) = match request.config.orientation {
-        Orientation::Portrait => (width, height),
-        Orientation::Landscape => (height, width),
-    };
+/// Renders the document and submits it to the configured backend (runs on a print worker)
+fn print_rendered(
+    app: &AppHandle,
+    task: &PrintTask,
+    document: &Document,
+    printer_id: Option<&str>,
+    options: JobOptions,
+) -> Result<PrintResult, PrintError> {
+    let pool = app.state::<BrowserPool>();
+    let backend = app.state::<SharedBackend>();
+
+    // Page ranges are checked against the rendered page count when submitting
+    let rendered = DocumentRenderer::new(&pool).with_task(task).render_pdf(document)?;
+    let job = print_job(document, rendered.data, rendered.page_count, options);
+
+    let result = submit_to_backend(backend.as_ref(), task, printer_id, job)?;
+    watch_submitted(task, backend.inner(), &result);
+
+    Ok(PrintResult { overflow: rendered.overflow.pages, ..result })
+}
+
+/// Renders the receipt and writes it to its destination (runs on a print worker)
+///
+/// A missing device or unreachable printer comes back as an unsuccessful `PrintResult`.
+fn print_rendered_receipt(app: &AppHandle, task: &PrintTask, request: &ReceiptRequest) -> Result<PrintResult, PrintError> {
+    let pool = app.state::<BrowserPool>();
+    let job = render_receipt(&DocumentRenderer::new(&pool).with_task(task), request)?;
 
-    // Convert mm to inches (Chrome uses inches)
-    let width_inches = (page_width / 25.4) as f64;
-    let height_inches = (page_height / 25.4) as f64;
-
-    let landscape = matches!(request.config.orientation, Orientation::Landscape);
-
-    // Create PDF options
-    use headless_chrome::types::PrintToPdfOptions;
-    
-    let mut pdf_options = PrintToPdfOptions::default();
-    pdf_options.landscape = Some(landscape);
-    pdf_options.display_header_footer = Some(false); // ← NO HEADERS/FOOTERS!
-    pdf_options.print_background = Some(true);
-    pdf_options.scale = Some(request.config.scale as f64);
-    pdf_options.paper_width = Some(width_inches);
-    pdf_options.paper_height = Some(height_inches);
-    // ← REMOVED: Chrome margins (we use CSS padding in HTML instead)
-    pdf_options.margin_top = Some(0.0);
-    pdf_options.margin_bottom = Some(0.0);
-    pdf_options.margin_left = Some(0.0);
-    pdf_options.margin_right = Some(0.0);
-    pdf_options.prefer_css_page_size = Some(true);
-
-    // Generate PDF
-    let pdf_data = tab.print_to_pdf(Some(pdf_options))
-        .map_err(|e| format!("Error generating PDF: {}", e))?;
-
-    // Save PDF
-    fs::write(&pdf_path, &pdf_data)
-        .map_err(|e| format!("Error saving PDF: {}", e))?;
-
-    Ok(pdf_path)
+    let printer = request.destination.to_string();
+    task.advance(PrintStage::Submitting { printer: printer.clone() })?;
+
+    match send_receipt(&request.destination, &job) {
+        Ok(()) => Ok(PrintResult {
+            success: true,
+            message: format!("Receipt sent to printer: {}", printer),
+            job_id: None,
+            printer: Some(printer),
+            job_state: None,
+            overflow: Vec::new(),
+            error: None,
+        }),
+        Err(error @ PrintError::PrinterUnavailable { .. }) => Ok(PrintResult::failed(error)),
+        Err(error) => Err(error),
+    }
 }
 
-/// Prepares full HTML with inline CSS
-fn prepare_full_html(content: &str, request: &PrintRequest) -> String {
-    let (width, height) = request.config.format.dimensions_mm();
-    let (page_width, page_height) = match request.config.orientation {
-        Orientation::Portrait => (width, height),
-        Orientation::Landscape => (height, width),
-    };
+/// Forwards progress to the frontend as [`PRINT_PROGRESS_EVENT`] events
+fn progress_sink(app: AppHandle) -> ProgressSink {
+    Arc::new(move |progress| {
+        let _ = app.emit(PRINT_PROGRESS_EVENT, progress);
+    })
+}
+
+/// Keeps reporting the state of an accepted job until it finishes
+fn watch_submitted(task: &PrintTask, backend: &SharedBackend, result: &PrintResult) {
+    if let (Some(printer), Some(job_id), Some(state)) = (&result.printer, &result.job_id, result.job_state) {
+        task.watch_job(backend.clone(), printer.clone(), job_id.clone(), state);
+    }
+}
 
-    format!(
-        r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <style>
-        * {{
-            margin: 0;
-            padding: 0;
-            box-sizing: border-box;
-        }}
-        
-        @page {{
-            size: {}mm {}mm;
-            margin: 0;
-        }}
-        
-        body {{
-            width: {}mm;
-            margin: 0;
-            padding: 0;
-            background: white;
-        }}
-        
-        .print-page {{
-            width: {}mm;
-            min-height: {}mm;
-            padding: {}mm;
-            background: white;
-            page-break-after: always;
-            font-family: 'Times New Roman', serif;
-            font-size: 12pt;
-            line-height: 1.5;
-        }}
-        
-        .print-page:last-child {{
-            page-break-after: avoid;
-        }}
-        
-        h1, h2, h3, h4, h5, h6 {{
-            margin-bottom: 0.5em;
-        }}
-        
-        p {{
-            margin-bottom: 0.5em;
-        }}
-    </style>
-</head>
-<body>
-{}
-</body>
-</html>"#,
-        page_width,
-        page_height,
-        page_width,
-        page_width,
-        page_height,
-        request.config.margins_mm,
-        content
-    )
+/// Wraps the rendered PDF, named after the document title
+///
+/// `page_count` comes from the render: flowing documents have more pages than `document`.
+fn print_job(document: &Document, data: Vec<u8>, page_count: usize, options: JobOptions) -> PrintJob {
+    PrintJob {
+        name: document.title().unwrap_or("Document").to_string(),
+        data,
+        page_count,
+        options,
+    }
 }
 
-/// Generates PDF with multiple pages using Chrome headless (no external dependencies!)
-/// Each page is treated individually but generated in a single PDF
-fn generate_multi_page_pdf(pages: &[String], config: &PrintConfig) -> Result<Vec<u8>, String> {
-    let (width, height) = config.format.dimensions_mm();
-    let (page_width, page_height) = match config.orientation {
-        Orientation::Portrait => (width, height),
-        Orientation::Landscape => (height, width),
+/// Sends a job to the requested printer, or to the backend's default printer
+///
+/// Job options are checked against the printer's capabilities first. Refusals by the
+/// print system come back as an unsuccessful `PrintResult`; failures to reach it at
+/// all are errors.
+fn submit_to_backend(
+    backend: &dyn PrintBackend,
+    task: &PrintTask,
+    printer_id: Option<&str>,
+    job: PrintJob,
+) -> Result<PrintResult, PrintError> {
+    job.options.validate(job.page_count)?;
+
+    let printer = match printer_id {
+        Some(printer_id) => printer_id.to_string(),
+        None => backend
+            .list_printers()?
+            .into_iter()
+            .find(|p| p.is_default)
+            .map(|p| p.id)
+            .ok_or(PrintError::NoDefaultPrinter)?,
     };
 
-    // Create HTML with multiple pages, each isolated with page-break
-    let mut pages_html = String::new();
-    for (idx, page_content) in pages.iter().enumerate() {
-        let page_break = if idx < pages.len() - 1 { "page-break-after: always;" } else { "" };
-        pages_html.push_str(&format!(
-            r#"<div class="print-page" style="
-                width: {}mm;
-                height: {}mm;
-                padding: {}mm;
-                background: white;
-                font-family: 'Times New Roman', serif;
-                font-size: 12pt;
-                line-height: 1.5;
-                box-sizing: border-box;
-                overflow: hidden;
-                {}
-            ">{}</div>"#,
-            page_width,
-            page_height,
-            config.margins_mm,
-            page_break,
-            page_content
-        ));
+    if job.options.needs_printer_support() {
+        job.options.check_supported(&printer, &backend.capabilities(&printer)?)?;
     }
 
-    let full_html = format!(
-        r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <style>
-        * {{
-            margin: 0;
-            padding: 0;
-            box-sizing: border-box;
-        }}
-        
-        @page {{
-            size: {}mm {}mm;
-            margin: 0;
-        }}
-        
-        body {{
-            margin: 0;
-            padding: 0;
-            background: white;
-        }}
-        
-        .print-page {{
-            page-break-inside: avoid;
-        }}
-        
-        h1, h2, h3, h4, h5, h6 {{
-            margin-bottom: 0.5em;
-        }}
-        
-        p {{
-            margin-bottom: 0.5em;
-        }}
-    </style>
-</head>
-<body>
-{}
-</body>
-</html>"#,
-        page_width,
-        page_height,
-        pages_html
-    );
-
-    // Start Chrome headless
-    let browser = Browser::default()
-        .map_err(|e| format!("Error starting Chrome headless: {}. Is Chrome/Chromium installed?", e))?;
-
-    let tab = browser.new_tab()
-        .map_err(|e| format!("Error creating new tab: {}", e))?;
-
-    // Convert HTML to data URI
-    let html_base64 = STANDARD.encode(full_html.as_bytes());
-    let data_uri = format!("data:text/html;base64,{}", html_base64);
-
-    // Navigate to HTML
-    tab.navigate_to(&data_uri)
-        .map_err(|e| format!("Error loading HTML: {}", e))?;
-
-    // Wait for complete loading
-    tab.wait_until_navigated()
-        .map_err(|e| format!("Error waiting for navigation: {}", e))?;
-
-    // Wait a bit more to ensure CSS/fonts are loaded
-    std::thread::sleep(std::time::Duration::from_millis(500));
-
-    // Convert mm to inches (Chrome uses inches)
-    let width_inches = (page_width / 25.4) as f64;
-    let height_inches = (page_height / 25.4) as f64;
-
-    let landscape = matches!(config.orientation, Orientation::Landscape);
-
-    // Create PDF options
-    use headless_chrome::types::PrintToPdfOptions;
-    
-    let mut pdf_options = PrintToPdfOptions::default();
-    pdf_options.landscape = Some(landscape);
-    pdf_options.display_header_footer = Some(false);
-    pdf_options.print_background = Some(true);
-    pdf_options.scale = Some(config.scale as f64);
-    pdf_options.paper_width = Some(width_inches);
-    pdf_options.paper_height = Some(height_inches);
-    pdf_options.margin_top = Some(0.0);
-    pdf_options.margin_bottom = Some(0.0);
-    pdf_options.margin_left = Some(0.0);
-    pdf_options.margin_right = Some(0.0);
-    pdf_options.prefer_css_page_size = Some(true);
-
-    // Generate PDF
-    let pdf_data = tab.print_to_pdf(Some(pdf_options))
-        .map_err(|e| format!("Error generating PDF: {}", e))?;
-
-    Ok(pdf_data)
+    task.advance(PrintStage::Submitting { printer: printer.clone() })?;
+
+    match backend.submit_job(&printer, job) {
+        Ok(status) => {
+            task.report(PrintStage::JobState { job_id: status.job_id.clone(), state: status.state });
+            Ok(PrintResult {
+                success: true,
+                message: format!("Document sent to printer: {}", printer),
+                job_id: Some(status.job_id),
+                printer: Some(printer),
+                job_state: Some(status.state),
+                overflow: Vec::new(),
+                error: None,
+            })
+        }
+        Err(error @ (PrintError::PrinterNotFound { .. }
+        | PrintError::PrinterUnavailable { .. }
+        | PrintError::JobRejected { .. })) => Ok(PrintResult::failed(error)),
+        Err(error) => Err(error),
+    }
 }
 
-/// Generates final PDF with all pages (each page treated individually)
-fn generate_merged_pdf(pages: &[String], config: &PrintConfig) -> Result<PathBuf, String> {
-    let temp_dir = std::env::temp_dir();
-    let job_id = uuid::Uuid::new_v4().to_string();
-    let pdf_path = temp_dir.join(format!("print-merged-{}.pdf", job_id));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::print_job::{JobState, PageRange, ScalingMode};
+    use crate::models::printer_capabilities::{ColorMode, Sides};
+    use crate::models::print_progress::PrintProgress;
+    use crate::services::print_backend::mock::MockBackend;
+    use std::sync::Mutex;
+
+    fn job() -> PrintJob {
+        PrintJob {
+            name: "Report".to_string(),
+            data: b"%PDF-1.7".to_vec(),
+            page_count: 3,
+            options: JobOptions::default(),
+        }
+    }
 
-    // Generate PDF with all pages using Chrome headless
-    let pdf_data = generate_multi_page_pdf(pages, config)?;
+    fn printer(id: &str, is_default: bool) -> PrinterInfo {
+        PrinterInfo { is_default, ..PrinterInfo::new(id, id) }
+    }
 
-    // Save final PDF
-    fs::write(&pdf_path, &pdf_data)
-        .map_err(|e| format!("Error saving PDF: {}", e))?;
+    #[test]
+    fn submits_to_the_default_printer() {
+        let backend = MockBackend::new(vec![printer("Office", false), printer("Lobby", true)]);
 
-    Ok(pdf_path)
-}
+        let result = submit_to_backend(&backend, &PrintTask::silent(), None, job()).unwrap();
 
-/// Saves document as PDF directly to specified path
-#[tauri::command]
-pub async fn save_pdf_to_path(request: PrintRequest, destination_path: String) -> Result<String, String> {
-    // 1. Create temporary PDF
-    let temp_pdf_path = html_to_pdf(&request.html_content, &request)?;
+        assert!(result.success);
+        assert_eq!(result.job_id.as_deref(), Some("1"));
+        assert_eq!(result.job_state, Some(JobState::Pending));
+        assert_eq!(result.message, "Document sent to printer: Lobby");
+        assert_eq!(backend.submitted()[0].1.data, b"%PDF-1.7");
+    }
 
-    // 2. Move temporary file to destination
-    fs::copy(&temp_pdf_path, &destination_path)
-        .map_err(|e| format!("Error saving PDF: {}", e))?;
+    #[test]
+    fn listed_jobs_follow_cancellation() {
+        let backend = MockBackend::new(vec![printer("Office", true), printer("Lobby", false)]);
+        submit_to_backend(&backend, &PrintTask::silent(), None, job()).unwrap();
+        submit_to_backend(&backend, &PrintTask::silent(), Some("Lobby"), job()).unwrap();
+        backend.cancel_job("Office", "1").unwrap();
+
+        let active = backend.list_jobs(None, JobFilter::Active).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].printer, "Lobby");
+        assert_eq!(active[0].name.as_deref(), Some("Report"));
+
+        let finished = backend.list_jobs(Some("Office"), JobFilter::Finished).unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].state, JobState::Canceled);
+        assert!(finished[0].completed_at.is_some());
+
+        assert!(backend.list_jobs(Some("Lobby"), JobFilter::Finished).unwrap().is_empty());
+        assert_eq!(backend.list_jobs(Some("Nope"), JobFilter::All).unwrap_err().code(), "printer_not_found");
+    }
 
-    // 3. Remove temporary
-    let _ = fs::remove_file(&temp_pdf_path);
+    #[test]
+    fn submission_is_reported_as_progress() {
+        let backend = MockBackend::default();
+        let tasks = PrintTasks::default();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let task = tasks.start(Some("task-1".to_string()), Arc::new(move |p: PrintProgress| recorded.lock().unwrap().push(p))).unwrap();
+
+        let result = submit_to_backend(&backend, &task, None, job()).unwrap();
+
+        assert_eq!(result.printer.as_deref(), Some("Mock_Printer"));
+        let stages: Vec<PrintStage> = events.lock().unwrap().iter().map(|p| p.stage.clone()).collect();
+        assert_eq!(stages, vec![
+            PrintStage::Submitting { printer: "Mock_Printer".to_string() },
+            PrintStage::JobState { job_id: "1".to_string(), state: JobState::Pending },
+        ]);
+    }
 
-    // 4. Return final path
-    Ok(destination_path)
-}
+    #[test]
+    fn canceled_prints_never_reach_the_backend() {
+        let backend = MockBackend::default();
+        let tasks = PrintTasks::default();
+        let task = tasks.start(Some("task-2".to_string()), Arc::new(|_| {})).unwrap();
 
-/// Saves document with multiple pages as PDF (merge in backend)
-#[tauri::command]
-pub async fn save_pdf_pages_to_path(request: PrintRequestPages, destination_path: String) -> Result<String, String> {
-    // 1. Generate merged PDF
-    let temp_pdf_path = generate_merged_pdf(&request.pages, &request.config)?;
+        tasks.cancel("task-2").unwrap();
+        let error = submit_to_backend(&backend, &task, None, job()).unwrap_err();
 
-    // 2. Move temporary file to destination
-    fs::copy(&temp_pdf_path, &destination_path)
-        .map_err(|e| format!("Error saving PDF: {}", e))?;
+        assert_eq!(error.code(), "print_canceled");
+        assert!(backend.submitted().is_empty());
+    }
 
-    // 3. Remove temporary
-    let _ = fs::remove_file(&temp_pdf_path);
+    #[test]
+    fn missing_default_printer_is_an_error() {
+        let backend = MockBackend::new(vec![printer("Office", false)]);
 
-    // 4. Return final path
-    Ok(destination_path)
-}
+        let error = submit_to_backend(&backend, &PrintTask::silent(), None, job()).unwrap_err();
 
-/// Prints document using CUPS (Linux)
-#[tauri::command]
-pub async fn print_document(request: PrintRequest) -> Result<PrintResult, String> {
-    // 1. Convert HTML to PDF
-    let pdf_path = html_to_pdf(&request.html_content, &request)?;
-
-    // 2. Determine target printer
-    let printer = if let Some(printer_id) = &request.printer_id {
-        printer_id.clone()
-    } else {
-        // Get default printer
-        let printers = get_printers().await?;
-        printers
-            .iter()
-            .find(|p| p.is_default)
-            .map(|p| p.id.clone())
-            .ok_or_else(|| "No default printer found".to_string())?
-    };
-
-    // 3. Send to printer (OS dependent)
-    #[cfg(target_os = "linux")]
-    let result = send_to_printer_linux(&pdf_path, &printer).await;
-    
-    #[cfg(target_os = "windows")]
-    let result = send_to_printer_windows(&pdf_path, &printer).await;
-    
-    #[cfg(target_os = "macos")]
-    let result = send_to_printer_macos(&pdf_path, &printer).await;
+        assert_eq!(error.code(), "no_default_printer");
+    }
 
-    // 4. Clean up temporary PDF
-    let _ = fs::remove_file(&pdf_path);
+    #[test]
+    fn unknown_printer_is_an_unsuccessful_result() {
+        let backend = MockBackend::default();
 
-    result
-}
+        let result = submit_to_backend(&backend, &PrintTask::silent(), Some("Nope"), job()).unwrap();
 
-/// Prints document with multiple pages (merge in backend)
-#[tauri::command]
-pub async fn print_document_pages(request: PrintRequestPages) -> Result<PrintResult, String> {
-    // 1. Generate merged PDF from all pages
-    let pdf_path = generate_merged_pdf(&request.pages, &request.config)?;
-
-    // 2. Determine target printer
-    let printer = if let Some(printer_id) = &request.printer_id {
-        printer_id.clone()
-    } else {
-        // Get default printer
-        let printers = get_printers().await?;
-        printers
-            .iter()
-            .find(|p| p.is_default)
-            .map(|p| p.id.clone())
-            .ok_or_else(|| "No default printer found".to_string())?
-    };
+        assert!(!result.success);
+        assert_eq!(result.error.unwrap().code(), "printer_not_found");
+        assert!(backend.submitted().is_empty());
+    }
 
-    // 3. Send to printer (OS dependent)
-    #[cfg(target_os = "linux")]
-    let result = send_to_printer_linux(&pdf_path, &printer).await;
-    
-    #[cfg(target_os = "windows")]
-    let result = send_to_printer_windows(&pdf_path, &printer).await;
-    
-    #[cfg(target_os = "macos")]
-    let result = send_to_printer_macos(&pdf_path, &printer).await;
+    #[test]
+    fn job_options_reach_the_backend() {
+        let backend = MockBackend::default();
+        let options = JobOptions {
+            copies: 2,
+            sides: Some(Sides::TwoSidedLongEdge),
+            page_ranges: vec![PageRange { from: 2, to: 3 }],
+            ..JobOptions::default()
+        };
 
-    // 4. Clean up temporary PDF
-    let _ = fs::remove_file(&pdf_path);
+        let result = submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options: options.clone(), ..job() }).unwrap();
 
-    result
-}
+        assert!(result.success);
+        assert_eq!(backend.submitted()[0].1.options, options);
+    }
 
-#[cfg(target_os = "linux")]
-async fn send_to_printer_linux(pdf_path: &std::path::Path, printer: &str) -> Result<PrintResult, String> {
-    let output = Command::new("lp")
-        .args(&[
-            "-d", printer,
-            "-o", "fit-to-page",
-        ])
-        .arg(pdf_path)
-        .output()
-        .map_err(|e| format!("Error executing lp: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Ok(PrintResult {
-            success: false,
-            message: format!("Failed to send to printer: {}", stderr),
-            job_id: None,
+    #[test]
+    fn unsupported_options_are_refused_before_submitting() {
+        let backend = MockBackend::default().with_capabilities(PrinterCapabilities {
+            sides: vec![Sides::OneSided],
+            color_modes: vec![ColorMode::Monochrome],
+            max_copies: Some(10),
+            ..PrinterCapabilities::unrestricted()
         });
+        let submit = |options: JobOptions| submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options, ..job() });
+
+        let duplex = submit(JobOptions { sides: Some(Sides::TwoSidedLongEdge), ..JobOptions::default() });
+        assert_eq!(duplex.unwrap_err().code(), "option_not_supported");
+        let color = submit(JobOptions { color_mode: Some(ColorMode::Color), ..JobOptions::default() });
+        assert_eq!(color.unwrap_err().code(), "option_not_supported");
+        let copies = submit(JobOptions { copies: 11, ..JobOptions::default() });
+        assert_eq!(copies.unwrap_err().code(), "option_not_supported");
+        assert!(backend.submitted().is_empty());
+
+        assert!(submit(JobOptions { sides: Some(Sides::OneSided), copies: 10, ..JobOptions::default() }).unwrap().success);
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let job_id = stdout.split_whitespace().last().map(|s| s.to_string());
+    #[test]
+    fn collated_copies_need_printer_support() {
+        let backend = MockBackend::default()
+            .with_capabilities(PrinterCapabilities { collate: false, ..PrinterCapabilities::unrestricted() });
+        let submit = |options: JobOptions| submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options, ..job() });
 
-    Ok(PrintResult {
-        success: true,
-        message: format!("Document sent to printer: {}", printer),
-        job_id,
-    })
-}
+        let collated = submit(JobOptions { copies: 2, collate: true, ..JobOptions::default() });
+        assert_eq!(collated.unwrap_err().code(), "option_not_supported");
+        assert!(backend.submitted().is_empty());
 
-#[cfg(target_os = "windows")]
-async fn send_to_printer_windows(pdf_path: &std::path::Path, printer: &str) -> Result<PrintResult, String> {
-    // On Windows, use PowerShell to print
-    let pdf_path_str = pdf_path.to_string_lossy();
-    
-    let ps_command = format!(
-        "Start-Process -FilePath '{}' -Verb Print -ArgumentList '/d:\"{}\"'",
-        pdf_path_str, printer
-    );
-
-    let output = Command::new("powershell")
-        .args(&["-Command", &ps_command])
-        .output()
-        .map_err(|e| format!("Error executing PowerShell: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Ok(PrintResult {
-            success: false,
-            message: format!("Failed to send to printer: {}", stderr),
-            job_id: None,
-        });
+        assert!(submit(JobOptions { copies: 2, collate: false, ..JobOptions::default() }).unwrap().success);
+        // A single copy has nothing to collate
+        assert!(submit(JobOptions { copies: 1, collate: true, ..JobOptions::default() }).unwrap().success);
     }
 
-    Ok(PrintResult {
-        success: true,
-        message: format!("Document sent to printer: {}", printer),
-        job_id: Some("windows-print-job".to_string()),
-    })
-}
-
-#[cfg(target_os = "macos")]
-async fn send_to_printer_macos(pdf_path: &std::path::Path, printer: &str) -> Result<PrintResult, String> {
-    let output = Command::new("lpr")
-        .args(&["-P", printer])
-        .arg(pdf_path)
-        .output()
-        .map_err(|e| format!("Error executing lpr: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Ok(PrintResult {
-            success: false,
-            message: format!("Failed to send to printer: {}", stderr),
-            job_id: None,
+    #[test]
+    fn scaling_modes_need_printer_support() {
+        let backend = MockBackend::default().with_capabilities(PrinterCapabilities {
+            scaling_modes: vec![ScalingMode::Fit, ScalingMode::Fill],
+            ..PrinterCapabilities::unrestricted()
         });
+        let submit = |scaling: ScalingMode| {
+            let options = JobOptions { scaling, ..JobOptions::default() };
+            submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options, ..job() })
+        };
+
+        assert_eq!(submit(ScalingMode::None).unwrap_err().code(), "option_not_supported");
+        assert!(backend.submitted().is_empty());
+
+        assert!(submit(ScalingMode::Fill).unwrap().success);
+        assert!(submit(ScalingMode::Fit).unwrap().success);
     }
 
-    Ok(PrintResult {
-        success: true,
-        message: format!("Document sent to printer: {}", printer),
-        job_id: None,
-    })
+    #[test]
+    fn page_ranges_of_flowing_requests_count_rendered_pages() {
+        let request: PrintRequest = serde_json::from_value(serde_json::json!({
+            "config": { "format": "A4", "orientation": "portrait", "margins_mm": 20.0, "scale": 1.0 },
+            "html_content": "<p>long report</p>",
+            "printer_id": null,
+            "options": { "page_ranges": [{ "from": 2, "to": 3 }] }
+        }))
+        .unwrap();
+        let document = Document::from(&request);
+        assert_eq!(document.pages.len(), 1);
+
+        // Pagination turned the single content into three pages
+        let job = print_job(&document, b"%PDF-1.7".to_vec(), 3, request.options.clone());
+        let result = submit_to_backend(&MockBackend::default(), &PrintTask::silent(), None, job).unwrap();
+
+        assert!(result.success);
+    }
+
+    #[test]
+    fn invalid_page_ranges_are_rejected() {
+        let backend = MockBackend::default();
+        let submit = |ranges: Vec<PageRange>| {
+            let options = JobOptions { page_ranges: ranges, ..JobOptions::default() };
+            submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options, ..job() })
+        };
+
+        for ranges in [
+            vec![PageRange { from: 0, to: 1 }],
+            vec![PageRange { from: 3, to: 2 }],
+            vec![PageRange { from: 2, to: 4 }],
+            vec![PageRange { from: 2, to: 3 }, PageRange { from: 1, to: 1 }],
+        ] {
+            assert_eq!(submit(ranges).unwrap_err().code(), "invalid_request");
+        }
+        let zero_copies = JobOptions { copies: 0, ..JobOptions::default() };
+        let error = submit_to_backend(&backend, &PrintTask::silent(), None, PrintJob { options: zero_copies, ..job() }).unwrap_err();
+        assert_eq!(error.code(), "invalid_request");
+    }
 }
@@ -1,7 +1,17 @@
 mod commands;
 mod models;
+mod services;
 
-use commands::print::{get_printers, print_document, print_document_pages, save_pdf_to_path, save_pdf_pages_to_path};
+use commands::print::{
+    cancel_job, cancel_print, check_page_overflow, get_job_status, get_printer_capabilities, get_printers, list_jobs, paginate_document,
+    print_document, print_document_pages, print_receipt, render_page_previews, save_pdf_to_path, save_pdf_pages_to_path,
+};
+use models::app_config::{AppConfig, APP_CONFIG_FILE};
+use services::browser_pool::BrowserPool;
+use services::print_backend;
+use services::print_task::PrintTasks;
+use services::print_workers::PrintWorkers;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,13 +19,42 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(BrowserPool::new())
+        .manage(PrintTasks::default())
+        .setup(|app| {
+            // Print backend comes from <app config dir>/settings.json (system spooler by default).
+            // Bad settings are logged and replaced by defaults rather than aborting the launch.
+            let config = AppConfig::load_or_default(&app.path().app_config_dir()?.join(APP_CONFIG_FILE));
+            let backend = print_backend::from_config(&config).or_else(|error| {
+                eprintln!("Ignoring print backend settings, using the system spooler: {}", error);
+                print_backend::from_config(&AppConfig::default())
+            })?;
+            app.manage(backend);
+            app.manage(PrintWorkers::new(&config.print_queue));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            cancel_job,
+            cancel_print,
+            check_page_overflow,
+            get_job_status,
+            get_printer_capabilities,
             get_printers,
+            list_jobs,
+            paginate_document,
             print_document,
             print_document_pages,
+            print_receipt,
+            render_page_previews,
             save_pdf_to_path,
             save_pdf_pages_to_path
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Terminate the shared Chrome headless process with the app
+            if let tauri::RunEvent::Exit = event {
+                app.state::<BrowserPool>().shutdown();
+            }
+        });
 }
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::models::print_error::PrintError;
+
+/// File name of the settings inside the app config directory
+pub const APP_CONFIG_FILE: &str = "settings.json";
+
+/// Application settings read at startup
+///
+/// Every field is optional, so a missing or partial file falls back to defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub print_backend: BackendConfig,
+    pub print_queue: QueueConfig,
+    /// Printers reached directly over the network, listed next to the backend's own
+    pub network_printers: Vec<NetworkPrinterConfig>,
+}
+
+/// Print backend selection, e.g. `{ "kind": "directory", "path": "/srv/print-out" }`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// The OS spooler (CUPS on Linux/macOS, PowerShell on Windows)
+    #[default]
+    System,
+    Cups,
+    /// Writes every job as a PDF into `path`
+    Directory { path: PathBuf },
+    /// Records jobs in memory without printing
+    Mock,
+}
+
+/// Limits of the print worker pool, e.g. `{ "max_concurrent_jobs": 2, "max_queued_jobs": 16 }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    /// Renders and print jobs running at the same time (each uses a Chrome tab)
+    pub max_concurrent_jobs: usize,
+    /// Jobs waiting for a free worker before new ones are refused
+    pub max_queued_jobs: usize,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig {
+            max_concurrent_jobs: 2,
+            max_queued_jobs: 16,
+        }
+    }
+}
+
+/// Printer without a spooler queue, e.g. `{ "id": "dock", "uri": "socket://10.0.0.7" }`
+///
+/// `uri` is `socket://host[:port]` for raw JetDirect printing (port 9100) or
+/// `lpd://host[:port]/queue` for an LPD queue (port 515).
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkPrinterConfig {
+    pub id: String,
+    /// Shown in the printer list instead of the id
+    pub name: Option<String>,
+    pub uri: String,
+    /// Seconds to wait for the printer to connect and acknowledge
+    #[serde(default = "default_network_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_network_timeout() -> u64 {
+    10
+}
+
+impl AppConfig {
+    /// Reads the settings file, using defaults when it does not exist
+    pub fn load(path: &Path) -> Result<Self, PrintError> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AppConfig::default()),
+            Err(e) => return Err(PrintError::io(path, e)),
+        };
+
+        serde_json::from_str(&json).map_err(|e| PrintError::InvalidRequest {
+            reason: format!("{}: {}", path.display(), e),
+        })
+    }
+
+    /// Reads the settings file, falling back to defaults when it can't be read or parsed
+    ///
+    /// A broken settings file must not keep the app from starting, so the error is
+    /// only logged.
+    pub fn load_or_default(path: &Path) -> Self {
+        AppConfig::load(path).unwrap_or_else(|error| {
+            eprintln!("Ignoring settings, using defaults: {}", error);
+            AppConfig::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_settings_fall_back_to_defaults() {
+        let path = std::env::temp_dir().join(format!("settings-{}.json", uuid::Uuid::new_v4()));
+        fs::write(&path, r#"{ "print_queue": { "max_concurrent_jobs": "two" "#).unwrap();
+
+        let error = AppConfig::load(&path).unwrap_err();
+        let config = AppConfig::load_or_default(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(error.code(), "invalid_request");
+        assert_eq!(config.print_queue.max_concurrent_jobs, QueueConfig::default().max_concurrent_jobs);
+        assert!(config.network_printers.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use crate::models::pdf_options::{OutlineSource, PdfConformance, PdfExportOptions, PdfMetadata, PdfProtection, PdfSignature};
+use crate::models::print_config::{PrintConfig, PrintRequest, PrintRequestPages};
+use crate::models::print_error::PrintError;
+
+/// Document to render: the HTML of each page plus the print configuration
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub pages: Vec<String>,
+    pub config: PrintConfig,
+    /// Written into the PDF after rendering, when set
+    pub metadata: Option<PdfMetadata>,
+    /// Adds a PDF outline after rendering, when set
+    pub outline: Option<OutlineSource>,
+    /// Encrypts the PDF after rendering, when set (saved PDFs only)
+    pub protection: Option<PdfProtection>,
+    /// Signs the PDF after rendering, when set (saved PDFs only)
+    pub signature: Option<PdfSignature>,
+    /// Converts the PDF to an archival standard after rendering, when set (saved PDFs only)
+    pub conformance: Option<PdfConformance>,
+    /// The only page is one long document, flowed across pages before rendering
+    pub flowing: bool,
+}
+
+impl Document {
+    pub fn new(pages: Vec<String>, config: PrintConfig) -> Self {
+        Document { pages, config, metadata: None, outline: None, protection: None, signature: None, conformance: None, flowing: false }
+    }
+
+    /// Title of the PDF metadata, falling back to `config.document_title`
+    pub fn title(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.title.as_deref())
+            .or(self.config.document_title.as_deref())
+    }
+
+    /// Adds the encryption, signature or PDF/A conversion of a saved PDF
+    ///
+    /// A signature covers the exact bytes of the file, so it can't be combined with
+    /// encryption. PDF/A forbids encryption, and the visible signature box is drawn
+    /// with a font that isn't embedded; invisible signatures are fine.
+    pub fn exported(self, options: PdfExportOptions) -> Result<Self, PrintError> {
+        let PdfExportOptions { protection, signature, conformance } = options;
+        if protection.is_some() && signature.is_some() {
+            return Err(PrintError::InvalidRequest {
+                reason: "a PDF cannot be both encrypted and signed".to_string(),
+            });
+        }
+        let visible_signature = signature.as_ref().is_some_and(|signature| signature.appearance.is_some());
+        if conformance.is_some() && (protection.is_some() || visible_signature) {
+            return Err(PrintError::InvalidRequest {
+                reason: "PDF/A does not allow encryption or a visible signature box".to_string(),
+            });
+        }
+        Ok(Document { protection, signature, conformance, ..self })
+    }
+}
+
+impl From<&PrintRequest> for Document {
+    /// Legacy single-content requests are paginated like `paginate_document` does
+    fn from(request: &PrintRequest) -> Self {
+        Document {
+            flowing: true,
+            ..Document::new(vec![request.html_content.clone()], request.config.clone())
+        }
+    }
+}
+
+impl From<&PrintRequestPages> for Document {
+    fn from(request: &PrintRequestPages) -> Self {
+        Document {
+            metadata: request.metadata.clone(),
+            outline: request.outline,
+            ..Document::new(request.pages.clone(), request.config.clone())
+        }
+    }
+}
+
+/// Content that does not fit in a page box and would be cut from the output
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PageOverflow {
+    pub page_index: usize,
+    pub overflow_mm: f32,
+}
+
+/// Per-page overflow measurement of a rendered document
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct OverflowReport {
+    /// Only pages whose content overflows, in page order
+    pub pages: Vec<PageOverflow>,
+}
+
+impl OverflowReport {
+    pub fn has_overflow(&self) -> bool {
+        !self.pages.is_empty()
+    }
+}
+
+/// Outcome of the PDF/A conversion of a saved PDF
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ConformanceReport {
+    pub conformance: PdfConformance,
+    /// No blockers: the XMP metadata claims conformance
+    pub conformant: bool,
+    /// What was changed to conform, e.g. removed JavaScript
+    pub changes: Vec<String>,
+    /// What could not be fixed and keeps the file from conforming
+    pub blockers: Vec<String>,
+}
+
+/// PDF written by the save commands
+#[derive(Debug, Serialize, Clone)]
+pub struct SavedPdf {
+    pub path: String,
+    /// Only when PDF/A was asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conformance: Option<ConformanceReport>,
+}
+
+/// Long HTML split into pages by the paginator
+#[derive(Debug, Serialize, Clone)]
+pub struct PaginatedDocument {
+    pub page_count: usize,
+    /// HTML of each page, ready to be sent back as `PrintRequestPages.pages`
+    pub pages: Vec<String>,
+}
+
+/// PNG image of one rendered page
+#[derive(Debug, Serialize, Clone)]
+pub struct PagePreview {
+    pub page_index: usize,
+    pub width_px: u32,
+    pub height_px: u32,
+    pub png_base64: String,
+}
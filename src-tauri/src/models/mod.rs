@@ -1,2 +1,10 @@
+pub mod app_config;
+pub mod document;
+pub mod pdf_options;
 pub mod print_config;
+pub mod print_error;
+pub mod print_job;
+pub mod print_progress;
+pub mod printer_capabilities;
+pub mod receipt;
 
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// Document information written into the PDF (Info dictionary and XMP metadata)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PdfMetadata {
+    /// Also used as the HTML `<title>` while rendering
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Vec<String>,
+    /// BCP 47 language tag (e.g. "en-US"), also set as `<html lang>` while rendering
+    pub language: Option<String>,
+    /// Creation date as Unix seconds; the time of export when None
+    pub created_at: Option<i64>,
+    /// Modification date as Unix seconds; `created_at` when None
+    ///
+    /// Fixing both dates makes repeated exports of the same content identical.
+    pub modified_at: Option<i64>,
+}
+
+/// What the PDF outline (bookmarks panel) is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlineSource {
+    /// `h1`–`h6` headings, nested by level
+    Headings,
+    /// Elements with a `data-bookmark` attribute: its value is the title (the element's
+    /// text if empty) and `data-bookmark-level` the nesting level (1 if absent)
+    Bookmarks,
+}
+
+/// Password protection of saved PDFs, encrypted with AES-256
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PdfProtection {
+    /// Needed to open the document; it opens without a password when None
+    pub user_password: Option<String>,
+    /// Lifts the restrictions below; a random one is generated when None
+    pub owner_password: Option<String>,
+    pub no_print: bool,
+    /// Also blocks text extraction, except for accessibility tools
+    pub no_copy: bool,
+    /// Blocks changes, annotations, form filling and page assembly
+    pub no_edit: bool,
+}
+
+/// Digital signature of saved PDFs (PAdES baseline B, SHA-256 with RSA)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PdfSignature {
+    /// PKCS#12 file (.p12/.pfx) holding the private key and its certificate chain
+    pub pkcs12_path: PathBuf,
+    pub password: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub contact_info: Option<String>,
+    /// Box showing the signer and date; the signature is invisible when None
+    #[serde(default)]
+    pub appearance: Option<SignatureAppearance>,
+}
+
+/// Position of the visible signature box, measured from the top-left corner of the page
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SignatureAppearance {
+    pub page_index: usize,
+    pub x_mm: f32,
+    pub y_mm: f32,
+    pub width_mm: f32,
+    pub height_mm: f32,
+}
+
+/// Archival standard saved PDFs are made to follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PdfConformance {
+    /// PDF/A-2b: visual appearance preserved, with embedded fonts, an output intent and XMP
+    #[serde(rename = "pdf_a_2b")]
+    PdfA2b,
+}
+
+impl PdfConformance {
+    /// `pdfaid:part` of the XMP claim
+    pub fn part(self) -> u8 {
+        match self {
+            PdfConformance::PdfA2b => 2,
+        }
+    }
+
+    /// `pdfaid:conformance` of the XMP claim
+    pub fn level(self) -> &'static str {
+        match self {
+            PdfConformance::PdfA2b => "B",
+        }
+    }
+}
+
+/// Options that only apply to saved PDFs, passed next to the request to the save commands
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PdfExportOptions {
+    pub protection: Option<PdfProtection>,
+    pub signature: Option<PdfSignature>,
+    pub conformance: Option<PdfConformance>,
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use crate::models::document::PageOverflow;
+use crate::models::pdf_options::{OutlineSource, PdfMetadata};
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobOptions, JobState};
 
 /// Page orientation
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Orientation {
     Portrait,
@@ -9,22 +13,234 @@ pub enum Orientation {
 }
 
 /// Supported paper format
-#[derive(Debug, Deserialize, Serialize, Clone)]
+///
+/// Deserializes from a format name (`"A4"`, `"legal"`, `"#10"`, ...), from explicit
+/// dimensions (`{ "width_mm": 80, "height_mm": 200 }`) or from `{ "Custom": { ... } }`.
+/// Named formats serialize back to their name, custom ones to their dimensions.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "PaperFormatRepr", into = "PaperFormatRepr")]
 pub enum PaperFormat {
-    A4,
-    A3,
+    // ISO 216 A series
+    A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10,
+    // ISO 216 B series
+    B0, B1, B2, B3, B4, B5, B6, B7, B8, B9, B10,
+    // ISO 269 C series (C5 is the common half-A4 envelope)
+    C0, C1, C2, C3, C4, C5, C6, C7, C8, C9, C10,
+    // North American sizes
     Letter,
+    Legal,
+    Tabloid,
+    Ledger,
+    Executive,
+    // Envelopes
+    DL,
+    Envelope10,
+    /// Any other size (receipts, labels, ...), given in portrait orientation
+    Custom { width_mm: f32, height_mm: f32 },
 }
 
+/// Largest accepted custom side (5 m), well beyond any roll or wide-format printer
+const MAX_CUSTOM_SIDE_MM: f32 = 5000.0;
+
+/// Every named format, used to resolve names when deserializing
+const NAMED_FORMATS: &[PaperFormat] = &[
+    PaperFormat::A0, PaperFormat::A1, PaperFormat::A2, PaperFormat::A3, PaperFormat::A4,
+    PaperFormat::A5, PaperFormat::A6, PaperFormat::A7, PaperFormat::A8, PaperFormat::A9,
+    PaperFormat::A10, PaperFormat::B0, PaperFormat::B1, PaperFormat::B2, PaperFormat::B3,
+    PaperFormat::B4, PaperFormat::B5, PaperFormat::B6, PaperFormat::B7, PaperFormat::B8,
+    PaperFormat::B9, PaperFormat::B10, PaperFormat::C0, PaperFormat::C1, PaperFormat::C2,
+    PaperFormat::C3, PaperFormat::C4, PaperFormat::C5, PaperFormat::C6, PaperFormat::C7,
+    PaperFormat::C8, PaperFormat::C9, PaperFormat::C10, PaperFormat::Letter,
+    PaperFormat::Legal, PaperFormat::Tabloid, PaperFormat::Ledger, PaperFormat::Executive,
+    PaperFormat::DL, PaperFormat::Envelope10,
+];
+
 impl PaperFormat {
     /// Returns dimensions in mm (width, height) in portrait mode
     pub fn dimensions_mm(&self) -> (f32, f32) {
         match self {
-            PaperFormat::A4 => (210.0, 297.0),
+            PaperFormat::A0 => (841.0, 1189.0),
+            PaperFormat::A1 => (594.0, 841.0),
+            PaperFormat::A2 => (420.0, 594.0),
             PaperFormat::A3 => (297.0, 420.0),
+            PaperFormat::A4 => (210.0, 297.0),
+            PaperFormat::A5 => (148.0, 210.0),
+            PaperFormat::A6 => (105.0, 148.0),
+            PaperFormat::A7 => (74.0, 105.0),
+            PaperFormat::A8 => (52.0, 74.0),
+            PaperFormat::A9 => (37.0, 52.0),
+            PaperFormat::A10 => (26.0, 37.0),
+            PaperFormat::B0 => (1000.0, 1414.0),
+            PaperFormat::B1 => (707.0, 1000.0),
+            PaperFormat::B2 => (500.0, 707.0),
+            PaperFormat::B3 => (353.0, 500.0),
+            PaperFormat::B4 => (250.0, 353.0),
+            PaperFormat::B5 => (176.0, 250.0),
+            PaperFormat::B6 => (125.0, 176.0),
+            PaperFormat::B7 => (88.0, 125.0),
+            PaperFormat::B8 => (62.0, 88.0),
+            PaperFormat::B9 => (44.0, 62.0),
+            PaperFormat::B10 => (31.0, 44.0),
+            PaperFormat::C0 => (917.0, 1297.0),
+            PaperFormat::C1 => (648.0, 917.0),
+            PaperFormat::C2 => (458.0, 648.0),
+            PaperFormat::C3 => (324.0, 458.0),
+            PaperFormat::C4 => (229.0, 324.0),
+            PaperFormat::C5 => (162.0, 229.0),
+            PaperFormat::C6 => (114.0, 162.0),
+            PaperFormat::C7 => (81.0, 114.0),
+            PaperFormat::C8 => (57.0, 81.0),
+            PaperFormat::C9 => (40.0, 57.0),
+            PaperFormat::C10 => (28.0, 40.0),
             PaperFormat::Letter => (215.9, 279.4),
+            PaperFormat::Legal => (215.9, 355.6),
+            PaperFormat::Tabloid => (279.4, 431.8),
+            // Ledger is the same sheet as Tabloid, usually printed in landscape
+            PaperFormat::Ledger => (279.4, 431.8),
+            PaperFormat::Executive => (184.15, 266.7),
+            PaperFormat::DL => (110.0, 220.0),
+            PaperFormat::Envelope10 => (104.775, 241.3),
+            PaperFormat::Custom { width_mm, height_mm } => (*width_mm, *height_mm),
         }
     }
+
+    /// Every named format
+    pub fn named() -> &'static [PaperFormat] {
+        NAMED_FORMATS
+    }
+
+    /// True if a sheet of the given size is this format, in either orientation
+    ///
+    /// Tolerates 1 mm, since printers report inch sizes rounded to whole mm.
+    pub fn matches_size(&self, width_mm: f32, height_mm: f32) -> bool {
+        let (w, h) = self.dimensions_mm();
+        let close = |a: f32, b: f32| (a - b).abs() <= 1.0;
+        (close(w, width_mm) && close(h, height_mm)) || (close(w, height_mm) && close(h, width_mm))
+    }
+
+    /// Name of the format, None for custom sizes
+    pub fn name(&self) -> Option<String> {
+        match self {
+            PaperFormat::Custom { .. } => None,
+            named => Some(format!("{:?}", named)),
+        }
+    }
+
+    /// Looks up a named format (case-insensitive, `#10` and `No10` alias `Envelope10`)
+    pub fn from_name(name: &str) -> Option<PaperFormat> {
+        let name = name.trim();
+        let name = match name.to_ascii_lowercase().as_str() {
+            "#10" | "no10" | "com10" => "Envelope10",
+            _ => name,
+        };
+
+        NAMED_FORMATS
+            .iter()
+            .find(|format| format.name().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .copied()
+    }
+}
+
+/// Wire representation of [`PaperFormat`]
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum PaperFormatRepr {
+    Name(String),
+    Dimensions { width_mm: f32, height_mm: f32 },
+    #[serde(rename_all = "PascalCase")]
+    Tagged { custom: PaperDimensions },
+}
+
+#[derive(Deserialize, Serialize)]
+struct PaperDimensions {
+    width_mm: f32,
+    height_mm: f32,
+}
+
+impl TryFrom<PaperFormatRepr> for PaperFormat {
+    type Error = String;
+
+    fn try_from(repr: PaperFormatRepr) -> Result<Self, Self::Error> {
+        let (width_mm, height_mm) = match repr {
+            PaperFormatRepr::Name(name) => {
+                return PaperFormat::from_name(&name)
+                    .ok_or_else(|| format!("Unknown paper format: {}", name));
+            }
+            PaperFormatRepr::Dimensions { width_mm, height_mm } => (width_mm, height_mm),
+            PaperFormatRepr::Tagged { custom } => (custom.width_mm, custom.height_mm),
+        };
+
+        let valid = |side: f32| side.is_finite() && side > 0.0 && side <= MAX_CUSTOM_SIDE_MM;
+        if !(valid(width_mm) && valid(height_mm)) {
+            return Err(format!("Invalid paper dimensions: {}mm x {}mm", width_mm, height_mm));
+        }
+
+        Ok(PaperFormat::Custom { width_mm, height_mm })
+    }
+}
+
+impl From<PaperFormat> for PaperFormatRepr {
+    fn from(format: PaperFormat) -> Self {
+        match format {
+            PaperFormat::Custom { width_mm, height_mm } => PaperFormatRepr::Dimensions { width_mm, height_mm },
+            named => PaperFormatRepr::Name(named.name().unwrap_or_default()),
+        }
+    }
+}
+
+/// Page margins in mm
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+    /// Binding-side margin for duplex printing (replaces left/right together with `outside`)
+    #[serde(default)]
+    pub inside: Option<f32>,
+    /// Outer-edge margin for duplex printing (replaces left/right together with `inside`)
+    #[serde(default)]
+    pub outside: Option<f32>,
+}
+
+impl Margins {
+    /// Same margin on all four sides
+    pub fn uniform(mm: f32) -> Self {
+        Margins {
+            top: mm,
+            right: mm,
+            bottom: mm,
+            left: mm,
+            inside: None,
+            outside: None,
+        }
+    }
+
+    /// Resolves the left/right margins of a page (0-based index)
+    ///
+    /// With mirrored margins, even indexes are right-hand pages (inside on the left)
+    /// and odd indexes are left-hand pages (inside on the right).
+    pub fn for_page(&self, page_index: usize) -> Margins {
+        let (left, right) = match (self.inside, self.outside) {
+            (Some(inside), Some(outside)) if page_index.is_multiple_of(2) => (inside, outside),
+            (Some(inside), Some(outside)) => (outside, inside),
+            _ => (self.left, self.right),
+        };
+
+        Margins {
+            top: self.top,
+            right,
+            bottom: self.bottom,
+            left,
+            inside: None,
+            outside: None,
+        }
+    }
+
+    /// CSS `padding` shorthand value (top right bottom left)
+    pub fn css_padding(&self) -> String {
+        format!("{}mm {}mm {}mm {}mm", self.top, self.right, self.bottom, self.left)
+    }
 }
 
 /// Print configuration
@@ -32,8 +248,78 @@ impl PaperFormat {
 pub struct PrintConfig {
     pub format: PaperFormat,
     pub orientation: Orientation,
+    /// Uniform margin, used when `margins` is not provided
+    #[serde(default)]
     pub margins_mm: f32,
+    /// Per-side margins (takes precedence over `margins_mm`)
+    #[serde(default)]
+    pub margins: Option<Margins>,
     pub scale: f32,
+    /// Maximum time to wait for fonts and images before rendering (ms)
+    #[serde(default = "default_render_timeout_ms")]
+    pub render_timeout_ms: u64,
+    /// Also wait until the page sets `window.__printReady = true`
+    #[serde(default)]
+    pub wait_for_print_ready: bool,
+    /// Running header HTML, supports `{{page}}`, `{{pages}}`, `{{date}}` and `{{title}}`
+    #[serde(default)]
+    pub header_template: Option<String>,
+    /// Running footer HTML, same placeholders as `header_template`
+    #[serde(default)]
+    pub footer_template: Option<String>,
+    /// Document title, used for `{{title}}` and the HTML `<title>`
+    #[serde(default)]
+    pub document_title: Option<String>,
+    /// Refuse to print or save while any page content overflows its page box
+    #[serde(default)]
+    pub fail_on_overflow: bool,
+}
+
+impl PrintConfig {
+    /// Page size in mm (width, height) after applying the orientation
+    pub fn page_size_mm(&self) -> (f32, f32) {
+        let (width, height) = self.format.dimensions_mm();
+        match self.orientation {
+            Orientation::Portrait => (width, height),
+            Orientation::Landscape => (height, width),
+        }
+    }
+
+    /// Margins to apply, falling back to the uniform `margins_mm`
+    pub fn effective_margins(&self) -> Margins {
+        self.margins.unwrap_or_else(|| Margins::uniform(self.margins_mm))
+    }
+
+    /// Returns true if a running header or footer is configured
+    pub fn has_header_footer(&self) -> bool {
+        self.header_template.is_some() || self.footer_template.is_some()
+    }
+
+    /// Checks that the margins are not negative and leave room for content on the paper
+    pub fn validate(&self) -> Result<(), PrintError> {
+        let invalid = |reason: String| Err(PrintError::InvalidRequest { reason });
+        let margins = self.effective_margins();
+        let (width, height) = self.page_size_mm();
+
+        let sides = [margins.top, margins.right, margins.bottom, margins.left];
+        let mirrored = [margins.inside, margins.outside];
+        if sides.into_iter().chain(mirrored.into_iter().flatten()).any(|mm| !(mm.is_finite() && mm >= 0.0)) {
+            return invalid("margins must not be negative".to_string());
+        }
+        if margins.top + margins.bottom >= height {
+            return invalid(format!("top and bottom margins leave no room on {}mm high paper", height));
+        }
+        // Mirrored pages use inside + outside instead of left + right
+        let horizontal = margins.for_page(0);
+        if horizontal.left + horizontal.right >= width {
+            return invalid(format!("left and right margins leave no room on {}mm wide paper", width));
+        }
+        Ok(())
+    }
+}
+
+fn default_render_timeout_ms() -> u64 {
+    10_000
 }
 
 /// Complete print request (kept for compatibility)
@@ -42,6 +328,11 @@ pub struct PrintRequest {
     pub config: PrintConfig,
     pub html_content: String,
     pub printer_id: Option<String>, // None = default printer
+    #[serde(default)]
+    pub options: JobOptions,
+    /// Id carried by progress events and accepted by `cancel_print` (generated if None)
+    #[serde(default)]
+    pub task_id: Option<String>,
 }
 
 /// Print request with multiple pages (each page is treated individually)
@@ -50,14 +341,96 @@ pub struct PrintRequestPages {
     pub config: PrintConfig,
     pub pages: Vec<String>, // Array of HTML contents, one for each page
     pub printer_id: Option<String>, // None = default printer
+    #[serde(default)]
+    pub options: JobOptions,
+    /// Title, author, language, ... of the produced PDF
+    #[serde(default)]
+    pub metadata: Option<PdfMetadata>,
+    /// Adds a PDF outline built from the page content
+    #[serde(default)]
+    pub outline: Option<OutlineSource>,
+    /// Id carried by progress events and accepted by `cancel_print` (generated if None)
+    #[serde(default)]
+    pub task_id: Option<String>,
+}
+
+/// Long HTML document to be flowed across pages automatically
+#[derive(Debug, Deserialize)]
+pub struct PaginateRequest {
+    pub config: PrintConfig,
+    pub html_content: String,
+}
+
+/// Request to render pages as preview images
+#[derive(Debug, Deserialize)]
+pub struct PreviewRequest {
+    pub config: PrintConfig,
+    pub pages: Vec<String>,
+    #[serde(default = "default_preview_dpi")]
+    pub dpi: f32,
+    /// Render only this page (0-based), None = all pages
+    #[serde(default)]
+    pub page_index: Option<usize>,
+}
+
+fn default_preview_dpi() -> f32 {
+    96.0
+}
+
+/// Whether a printer is ready, busy or stopped (IPP `printer-state`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrinterState {
+    Idle,
+    Printing,
+    /// Paused, disabled or offline
+    Stopped,
+}
+
+impl PrinterState {
+    /// Maps the IPP `printer-state` enum value (3..=5)
+    pub fn from_ipp(value: i32) -> Option<Self> {
+        match value {
+            3 => Some(PrinterState::Idle),
+            4 => Some(PrinterState::Printing),
+            5 => Some(PrinterState::Stopped),
+            _ => None,
+        }
+    }
 }
 
 /// Information about an available printer
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct PrinterInfo {
     pub id: String,           // Technical name (e.g.: "Canon_G3000")
     pub display_name: String, // Friendly name
     pub is_default: bool,
+    pub state: PrinterState,
+    /// IPP `printer-state-reasons` keywords (e.g. "media-empty-error"), empty when all is well
+    pub state_reasons: Vec<String>,
+    /// False while the queue rejects new jobs
+    pub accepting_jobs: bool,
+    pub location: Option<String>,
+    pub make_and_model: Option<String>,
+    /// Description set by the administrator (IPP `printer-info`)
+    pub description: Option<String>,
+}
+
+impl PrinterInfo {
+    /// Idle printer accepting jobs, with nothing known beyond its names
+    pub fn new(id: impl Into<String>, display_name: impl Into<String>) -> Self {
+        PrinterInfo {
+            id: id.into(),
+            display_name: display_name.into(),
+            is_default: false,
+            state: PrinterState::Idle,
+            state_reasons: Vec::new(),
+            accepting_jobs: true,
+            location: None,
+            make_and_model: None,
+            description: None,
+        }
+    }
 }
 
 /// Result of a print operation
@@ -66,4 +439,89 @@ pub struct PrintResult {
     pub success: bool,
     pub message: String,
     pub job_id: Option<String>,
+    /// Printer the job went to, also when the default printer was used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub printer: Option<String>,
+    /// State reported by the print system when the job was accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_state: Option<JobState>,
+    /// Pages whose content was cut off by the page box
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overflow: Vec<PageOverflow>,
+    /// Typed failure reason when `success` is false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<PrintError>,
+}
+
+impl PrintResult {
+    /// Unsuccessful result carrying the typed error
+    pub fn failed(error: PrintError) -> Self {
+        PrintResult {
+            success: false,
+            message: error.to_string(),
+            job_id: None,
+            printer: None,
+            job_state: None,
+            overflow: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(value: serde_json::Value) -> Result<PaperFormat, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn named_formats_are_portrait() {
+        for named in PaperFormat::named() {
+            let (width, height) = named.dimensions_mm();
+            assert!(width < height, "{:?} is not portrait", named);
+        }
+        assert_eq!(PaperFormat::Ledger.dimensions_mm(), PaperFormat::Tabloid.dimensions_mm());
+    }
+
+    #[test]
+    fn margins_must_leave_room_on_the_paper() {
+        let config = |margins: Margins| -> PrintConfig {
+            let mut config: PrintConfig = serde_json::from_value(serde_json::json!({
+                "format": "A6",
+                "orientation": "landscape",
+                "margins_mm": 0.0,
+                "scale": 1.0
+            }))
+            .unwrap();
+            config.margins = Some(margins);
+            config
+        };
+        let code = |margins: Margins| config(margins).validate().unwrap_err().code();
+        let mirrored = |inside, outside| Margins {
+            inside: Some(inside),
+            outside: Some(outside),
+            ..Margins::uniform(10.0)
+        };
+
+        assert!(config(Margins::uniform(10.0)).validate().is_ok());
+        assert_eq!(code(Margins { left: -1.0, ..Margins::uniform(10.0) }), "invalid_request");
+        assert_eq!(code(mirrored(-1.0, 5.0)), "invalid_request");
+        // Landscape A6 is 148mm wide and 105mm high
+        assert_eq!(code(Margins { top: 60.0, bottom: 45.0, ..Margins::uniform(10.0) }), "invalid_request");
+        assert!(config(Margins { left: 70.0, right: 70.0, ..Margins::uniform(10.0) }).validate().is_ok());
+        assert_eq!(code(mirrored(100.0, 48.0)), "invalid_request");
+    }
+
+    #[test]
+    fn custom_sizes_must_be_positive_and_sane() {
+        assert_eq!(
+            format(serde_json::json!({ "width_mm": 80, "height_mm": 200 })).unwrap(),
+            PaperFormat::Custom { width_mm: 80.0, height_mm: 200.0 }
+        );
+        assert!(format(serde_json::json!({ "width_mm": 0, "height_mm": 200 })).is_err());
+        assert!(format(serde_json::json!({ "width_mm": 80, "height_mm": -1 })).is_err());
+        assert!(format(serde_json::json!({ "Custom": { "width_mm": 80, "height_mm": 1e9 } })).is_err());
+    }
 }
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::io;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use crate::models::document::PageOverflow;
+
+/// Error returned by print commands
+///
+/// Crosses the IPC boundary as `{ code, message, details }`, where `code` is a
+/// stable snake_case identifier the frontend can match on.
+#[derive(Debug, Clone)]
+pub enum PrintError {
+    /// No Chrome/Chromium executable could be found
+    ChromeNotFound { reason: String },
+    /// Chrome is installed but failed while starting, loading or printing
+    BrowserFailed { stage: String, reason: String },
+    /// Fonts, images or `window.__printReady` did not settle in time
+    RenderTimeout { timeout_ms: u64, pending: Vec<String> },
+    /// Page content does not fit its page box and `fail_on_overflow` is set
+    ContentOverflow { pages: Vec<PageOverflow> },
+    /// Chrome's PDF could not be post-processed (metadata, outline, ...)
+    PdfProcessingFailed { step: String, reason: String },
+    /// The request itself is invalid
+    InvalidRequest { reason: String },
+    /// The print call was canceled with `cancel_print` before reaching the printer
+    PrintCanceled { task_id: String },
+    /// Too many print jobs are already running or waiting
+    QueueFull { limit: usize },
+    /// A print job panicked or the worker threads are gone
+    Internal { reason: String },
+    /// No printer was given and the system has no default printer
+    NoDefaultPrinter,
+    /// The requested printer does not exist
+    PrinterNotFound { printer: String },
+    /// The printer exists but is offline, disabled or not accepting jobs
+    PrinterUnavailable { printer: String, reason: String },
+    /// The print system (CUPS, PowerShell, ...) could not be reached
+    SpoolerUnavailable { command: String, reason: String },
+    /// The printer cannot honour a job option (duplex, color, copies, ...)
+    OptionNotSupported { printer: String, option: String },
+    /// The print system refused the job
+    JobRejected { printer: String, reason: String },
+    /// The print system does not know the job (wrong id, or already purged)
+    JobNotFound { job_id: String },
+    /// Not enough disk space to write a file
+    DiskFull { path: String },
+    /// A file could not be written or read because of permissions
+    PermissionDenied { path: String },
+    /// Any other I/O failure
+    Io { path: String, reason: String },
+}
+
+impl PrintError {
+    /// Stable machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            PrintError::ChromeNotFound { .. } => "chrome_not_found",
+            PrintError::BrowserFailed { .. } => "browser_failed",
+            PrintError::RenderTimeout { .. } => "render_timeout",
+            PrintError::ContentOverflow { .. } => "content_overflow",
+            PrintError::PdfProcessingFailed { .. } => "pdf_processing_failed",
+            PrintError::InvalidRequest { .. } => "invalid_request",
+            PrintError::PrintCanceled { .. } => "print_canceled",
+            PrintError::QueueFull { .. } => "queue_full",
+            PrintError::Internal { .. } => "internal_error",
+            PrintError::NoDefaultPrinter => "no_default_printer",
+            PrintError::PrinterNotFound { .. } => "printer_not_found",
+            PrintError::PrinterUnavailable { .. } => "printer_unavailable",
+            PrintError::SpoolerUnavailable { .. } => "spooler_unavailable",
+            PrintError::OptionNotSupported { .. } => "option_not_supported",
+            PrintError::JobRejected { .. } => "job_rejected",
+            PrintError::JobNotFound { .. } => "job_not_found",
+            PrintError::DiskFull { .. } => "disk_full",
+            PrintError::PermissionDenied { .. } => "permission_denied",
+            PrintError::Io { .. } => "io_error",
+        }
+    }
+
+    /// Structured details for the frontend
+    pub fn details(&self) -> Value {
+        match self {
+            PrintError::ChromeNotFound { reason } => json!({ "reason": reason }),
+            PrintError::BrowserFailed { stage, reason } => json!({ "stage": stage, "reason": reason }),
+            PrintError::RenderTimeout { timeout_ms, pending } => {
+                json!({ "timeout_ms": timeout_ms, "pending": pending })
+            }
+            PrintError::ContentOverflow { pages } => json!({ "pages": pages }),
+            PrintError::PdfProcessingFailed { step, reason } => json!({ "step": step, "reason": reason }),
+            PrintError::InvalidRequest { reason } => json!({ "reason": reason }),
+            PrintError::PrintCanceled { task_id } => json!({ "task_id": task_id }),
+            PrintError::QueueFull { limit } => json!({ "limit": limit }),
+            PrintError::Internal { reason } => json!({ "reason": reason }),
+            PrintError::NoDefaultPrinter => Value::Null,
+            PrintError::PrinterNotFound { printer } => json!({ "printer": printer }),
+            PrintError::PrinterUnavailable { printer, reason }
+            | PrintError::JobRejected { printer, reason } => {
+                json!({ "printer": printer, "reason": reason })
+            }
+            PrintError::SpoolerUnavailable { command, reason } => {
+                json!({ "command": command, "reason": reason })
+            }
+            PrintError::OptionNotSupported { printer, option } => {
+                json!({ "printer": printer, "option": option })
+            }
+            PrintError::JobNotFound { job_id } => json!({ "job_id": job_id }),
+            PrintError::DiskFull { path } | PrintError::PermissionDenied { path } => {
+                json!({ "path": path })
+            }
+            PrintError::Io { path, reason } => json!({ "path": path, "reason": reason }),
+        }
+    }
+
+    /// Chrome failure at the given stage (e.g. "new_tab", "navigate", "print_to_pdf")
+    pub fn browser(stage: &str, error: impl fmt::Display) -> Self {
+        PrintError::BrowserFailed {
+            stage: stage.to_string(),
+            reason: error.to_string(),
+        }
+    }
+
+    /// PDF post-processing failure at the given step (e.g. "load", "metadata", "save")
+    pub fn pdf(step: &str, error: impl fmt::Display) -> Self {
+        PrintError::PdfProcessingFailed {
+            step: step.to_string(),
+            reason: error.to_string(),
+        }
+    }
+
+    /// Classifies an I/O error on `path` (disk full, permission denied, ...)
+    pub fn io(path: impl AsRef<std::path::Path>, error: io::Error) -> Self {
+        let path = path.as_ref().to_string_lossy().to_string();
+        match error.kind() {
+            io::ErrorKind::StorageFull => PrintError::DiskFull { path },
+            io::ErrorKind::PermissionDenied => PrintError::PermissionDenied { path },
+            _ => PrintError::Io {
+                path,
+                reason: error.to_string(),
+            },
+        }
+    }
+
+    /// Failure to run a print system command (lp, lpstat, PowerShell, ...)
+    pub fn spooler(command: &str, error: impl fmt::Display) -> Self {
+        PrintError::SpoolerUnavailable {
+            command: command.to_string(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintError::ChromeNotFound { reason } => {
+                write!(f, "Chrome/Chromium is not installed: {}", reason)
+            }
+            PrintError::BrowserFailed { stage, reason } => {
+                write!(f, "Chrome headless failed ({}): {}", stage, reason)
+            }
+            PrintError::RenderTimeout { timeout_ms, pending } => write!(
+                f,
+                "Timed out after {} ms waiting for page to render (still pending: {})",
+                timeout_ms,
+                pending.join(", ")
+            ),
+            PrintError::ContentOverflow { pages } => {
+                let list: Vec<String> = pages
+                    .iter()
+                    .map(|p| format!("page {} by {:.1} mm", p.page_index + 1, p.overflow_mm))
+                    .collect();
+                write!(f, "Content does not fit the page: {}", list.join(", "))
+            }
+            PrintError::PdfProcessingFailed { step, reason } => {
+                write!(f, "PDF post-processing failed ({}): {}", step, reason)
+            }
+            PrintError::InvalidRequest { reason } => write!(f, "Invalid print request: {}", reason),
+            PrintError::PrintCanceled { task_id } => write!(f, "Print {} was canceled", task_id),
+            PrintError::QueueFull { limit } => {
+                write!(f, "Too many print jobs in progress ({} running or waiting)", limit)
+            }
+            PrintError::Internal { reason } => write!(f, "Internal error: {}", reason),
+            PrintError::NoDefaultPrinter => write!(f, "No default printer found"),
+            PrintError::PrinterNotFound { printer } => write!(f, "Printer not found: {}", printer),
+            PrintError::PrinterUnavailable { printer, reason } => {
+                write!(f, "Printer {} is unavailable: {}", printer, reason)
+            }
+            PrintError::SpoolerUnavailable { command, reason } => {
+                write!(f, "Error executing {}: {}", command, reason)
+            }
+            PrintError::OptionNotSupported { printer, option } => {
+                write!(f, "Printer {} does not support {}", printer, option)
+            }
+            PrintError::JobRejected { printer, reason } => {
+                write!(f, "Failed to send to printer {}: {}", printer, reason)
+            }
+            PrintError::JobNotFound { job_id } => write!(f, "Print job not found: {}", job_id),
+            PrintError::DiskFull { path } => write!(f, "Not enough disk space to write {}", path),
+            PrintError::PermissionDenied { path } => write!(f, "Permission denied: {}", path),
+            PrintError::Io { path, reason } => write!(f, "Error accessing {}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for PrintError {}
+
+impl Serialize for PrintError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PrintError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
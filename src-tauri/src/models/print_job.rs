@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use crate::models::print_error::PrintError;
+use crate::models::printer_capabilities::{ColorMode, PrinterCapabilities, Sides};
+
+/// Lifecycle state of a print job (IPP `job-state`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Pending,
+    Held,
+    Processing,
+    Stopped,
+    Canceled,
+    Aborted,
+    Completed,
+}
+
+impl JobState {
+    /// Maps the IPP `job-state` enum value (3..=9)
+    pub fn from_ipp(value: i32) -> Option<Self> {
+        match value {
+            3 => Some(JobState::Pending),
+            4 => Some(JobState::Held),
+            5 => Some(JobState::Processing),
+            6 => Some(JobState::Stopped),
+            7 => Some(JobState::Canceled),
+            8 => Some(JobState::Aborted),
+            9 => Some(JobState::Completed),
+            _ => None,
+        }
+    }
+
+    /// True once the job will not change state anymore
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobState::Canceled | JobState::Aborted | JobState::Completed)
+    }
+}
+
+/// Job as reported by the print system
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub job_id: String,
+    /// Printer the job was sent to
+    pub printer: String,
+    /// Job name shown in the queue
+    pub name: Option<String>,
+    pub state: JobState,
+    /// IPP `job-state-reasons` keywords (e.g. "job-printing", "job-completed-successfully")
+    pub state_reasons: Vec<String>,
+    /// Unix time the job was queued
+    pub created_at: Option<i64>,
+    /// Unix time the job reached a final state
+    pub completed_at: Option<i64>,
+}
+
+/// Which jobs `list_jobs` returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobFilter {
+    /// Pending, held, processing or stopped jobs
+    #[default]
+    Active,
+    /// Completed, canceled or aborted jobs
+    Finished,
+    All,
+}
+
+impl JobFilter {
+    pub fn matches(&self, state: JobState) -> bool {
+        match self {
+            JobFilter::Active => !state.is_terminal(),
+            JobFilter::Finished => state.is_terminal(),
+            JobFilter::All => true,
+        }
+    }
+}
+
+/// How the page is fitted to the printable area (IPP `print-scaling`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMode {
+    /// Shrink or grow to fit inside the printable area
+    #[default]
+    Fit,
+    /// Print at 100%, clipping what falls outside the printable area
+    None,
+    /// Fill the printable area, cropping the overlap
+    Fill,
+}
+
+impl ScalingMode {
+    pub fn from_ipp(keyword: &str) -> Option<Self> {
+        match keyword {
+            "fit" => Some(ScalingMode::Fit),
+            "none" => Some(ScalingMode::None),
+            "fill" => Some(ScalingMode::Fill),
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive range of 1-based page numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRange {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// Copies, duplex, color, page ranges and scaling of a print job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobOptions {
+    pub copies: u32,
+    /// Print copies as complete sets (1,2,3,1,2,3) rather than page by page (1,1,2,2,3,3)
+    pub collate: bool,
+    /// None keeps the printer default
+    pub sides: Option<Sides>,
+    /// None keeps the printer default
+    pub color_mode: Option<ColorMode>,
+    /// Pages to print, in ascending order; empty prints every page
+    pub page_ranges: Vec<PageRange>,
+    pub scaling: ScalingMode,
+}
+
+impl Default for JobOptions {
+    fn default() -> Self {
+        JobOptions {
+            copies: 1,
+            collate: true,
+            sides: None,
+            color_mode: None,
+            page_ranges: Vec::new(),
+            scaling: ScalingMode::Fit,
+        }
+    }
+}
+
+impl JobOptions {
+    /// Checks the options against the document they apply to
+    pub fn validate(&self, page_count: usize) -> Result<(), PrintError> {
+        let invalid = |reason: String| Err(PrintError::InvalidRequest { reason });
+
+        if self.copies == 0 {
+            return invalid("copies must be at least 1".to_string());
+        }
+
+        let mut previous_to = 0;
+        for range in &self.page_ranges {
+            if range.from == 0 || range.from > range.to {
+                return invalid(format!("invalid page range {}-{}", range.from, range.to));
+            }
+            if range.to as usize > page_count {
+                return invalid(format!(
+                    "page range {}-{} is outside the document ({} pages)",
+                    range.from, range.to, page_count
+                ));
+            }
+            if range.from <= previous_to {
+                return invalid("page ranges must be ascending and must not overlap".to_string());
+            }
+            previous_to = range.to;
+        }
+
+        Ok(())
+    }
+
+    /// True if the options ask for more than every printer can do
+    ///
+    /// Collation only matters with several copies, so it is covered by `copies`;
+    /// fitting to the page is what every printer does.
+    pub fn needs_printer_support(&self) -> bool {
+        self.copies > 1
+            || self.sides.is_some()
+            || self.color_mode.is_some()
+            || !self.page_ranges.is_empty()
+            || self.scaling != ScalingMode::Fit
+    }
+
+    /// Checks the options against what `printer` supports
+    pub fn check_supported(&self, printer: &str, capabilities: &PrinterCapabilities) -> Result<(), PrintError> {
+        let unsupported = |option: String| {
+            Err(PrintError::OptionNotSupported {
+                printer: printer.to_string(),
+                option,
+            })
+        };
+
+        if let Some(max_copies) = capabilities.max_copies {
+            if self.copies > max_copies {
+                return unsupported(format!("{} copies (at most {})", self.copies, max_copies));
+            }
+        }
+        if let Some(sides) = self.sides {
+            if !capabilities.sides.contains(&sides) {
+                return unsupported(format!("sides {:?}", sides));
+            }
+        }
+        if let Some(color_mode) = self.color_mode {
+            if !capabilities.color_modes.contains(&color_mode) {
+                return unsupported(format!("color mode {:?}", color_mode));
+            }
+        }
+        if !self.page_ranges.is_empty() && !capabilities.page_ranges {
+            return unsupported("page ranges".to_string());
+        }
+        if self.copies > 1 && self.collate && !capabilities.collate {
+            return unsupported("collated copies".to_string());
+        }
+        if self.scaling != ScalingMode::Fit && !capabilities.scaling_modes.contains(&self.scaling) {
+            return unsupported(format!("scaling {:?}", self.scaling));
+        }
+
+        Ok(())
+    }
+}
+
+/// Rendered document handed to a print backend
+#[derive(Debug, Clone)]
+pub struct PrintJob {
+    /// Job name shown in the printer queue
+    pub name: String,
+    /// PDF bytes
+    pub data: Vec<u8>,
+    /// Number of pages in `data`
+    pub page_count: usize,
+    pub options: JobOptions,
+}
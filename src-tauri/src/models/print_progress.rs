@@ -0,0 +1,36 @@
+use serde::Serialize;
+use crate::models::print_job::JobState;
+
+/// Name of the Tauri event carrying [`PrintProgress`]
+pub const PRINT_PROGRESS_EVENT: &str = "print-progress";
+
+/// Stage reached by a print or save call
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PrintStage {
+    /// Waiting for a free print worker
+    Queued,
+    /// Getting a Chrome tab, launching Chrome first if it is not running
+    StartingBrowser,
+    /// Loading the HTML and waiting for fonts and images
+    LoadingHtml,
+    /// Chrome is printing the pages to PDF, starting at 1-based `page` of `pages`
+    ///
+    /// Sent for each batch of pages, so long documents report progress while rendering.
+    Rendering { page: usize, pages: usize },
+    /// Writing the PDF to its destination file
+    WritingPdf,
+    /// Handing the PDF to the print backend
+    Submitting { printer: String },
+    /// The print system reported a new state for the submitted job
+    JobState { job_id: String, state: JobState },
+}
+
+/// Payload of [`PRINT_PROGRESS_EVENT`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrintProgress {
+    /// Id of the print call (`task_id` of the request)
+    pub task_id: String,
+    #[serde(flatten)]
+    pub stage: PrintStage,
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use crate::models::print_config::{Orientation, PaperFormat};
+use crate::models::print_job::ScalingMode;
+
+/// Two-sided printing mode (IPP `sides`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sides {
+    OneSided,
+    TwoSidedLongEdge,
+    TwoSidedShortEdge,
+}
+
+impl Sides {
+    pub fn from_ipp(keyword: &str) -> Option<Self> {
+        match keyword {
+            "one-sided" => Some(Sides::OneSided),
+            "two-sided-long-edge" => Some(Sides::TwoSidedLongEdge),
+            "two-sided-short-edge" => Some(Sides::TwoSidedShortEdge),
+            _ => None,
+        }
+    }
+}
+
+/// Color or grayscale output (IPP `print-color-mode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    Color,
+    Monochrome,
+}
+
+impl ColorMode {
+    pub fn from_ipp(keyword: &str) -> Option<Self> {
+        match keyword {
+            "color" => Some(ColorMode::Color),
+            "monochrome" | "auto-monochrome" | "process-monochrome" => Some(ColorMode::Monochrome),
+            _ => None,
+        }
+    }
+}
+
+/// Paper size the printer can feed, in portrait orientation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaSize {
+    /// PWG self-describing name (e.g. "iso_a4_210x297mm")
+    pub name: String,
+    pub width_mm: f32,
+    pub height_mm: f32,
+    /// Matching named format, None for sizes without a [`PaperFormat`]
+    pub format: Option<PaperFormat>,
+}
+
+/// Printer resolution in dots per inch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Resolution {
+    pub x_dpi: u32,
+    pub y_dpi: u32,
+}
+
+/// Smallest margins the printer can print to, in mm (0 for borderless)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct HardwareMargins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+/// What a printer supports, as reported by its backend
+#[derive(Debug, Clone, Serialize)]
+pub struct PrinterCapabilities {
+    pub media: Vec<MediaSize>,
+    /// Name of the default media, as in [`MediaSize::name`]
+    pub default_media: Option<String>,
+    /// Named formats the printer can feed, for the format picker
+    pub paper_formats: Vec<PaperFormat>,
+    /// Whether sizes outside `media` (receipts, labels, ...) are accepted
+    pub custom_sizes: bool,
+    pub orientations: Vec<Orientation>,
+    pub sides: Vec<Sides>,
+    pub color_modes: Vec<ColorMode>,
+    pub resolutions: Vec<Resolution>,
+    /// Highest number of copies per job, None if unknown
+    pub max_copies: Option<u32>,
+    /// Whether only some pages of a document can be printed
+    pub page_ranges: bool,
+    /// Whether multiple copies can be printed as complete sets
+    pub collate: bool,
+    pub scaling_modes: Vec<ScalingMode>,
+    /// Input trays / media sources (e.g. "auto", "main", "manual")
+    pub input_trays: Vec<String>,
+    pub hardware_margins_mm: HardwareMargins,
+}
+
+impl PrinterCapabilities {
+    /// Everything the renderer can produce, for destinations without physical limits
+    pub fn unrestricted() -> Self {
+        PrinterCapabilities {
+            media: Vec::new(),
+            default_media: None,
+            paper_formats: PaperFormat::named().to_vec(),
+            custom_sizes: true,
+            orientations: vec![Orientation::Portrait, Orientation::Landscape],
+            sides: vec![Sides::OneSided, Sides::TwoSidedLongEdge, Sides::TwoSidedShortEdge],
+            color_modes: vec![ColorMode::Color, ColorMode::Monochrome],
+            resolutions: Vec::new(),
+            max_copies: None,
+            page_ranges: true,
+            collate: true,
+            scaling_modes: vec![ScalingMode::Fit, ScalingMode::None, ScalingMode::Fill],
+            input_trays: Vec::new(),
+            hardware_margins_mm: HardwareMargins::default(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::path::PathBuf;
+use serde::Deserialize;
+use crate::services::raw_socket::RAW_PORT;
+
+/// Thermal paper roll width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ReceiptWidth {
+    #[serde(rename = "58mm")]
+    Mm58,
+    #[serde(rename = "80mm")]
+    Mm80,
+}
+
+impl ReceiptWidth {
+    /// Printable width in mm (the print head is narrower than the roll)
+    pub fn printable_mm(&self) -> f32 {
+        match self {
+            ReceiptWidth::Mm58 => 48.0,
+            ReceiptWidth::Mm80 => 72.0,
+        }
+    }
+
+    /// Printable width in dots at the usual 203 dpi (8 dots per mm)
+    pub fn dots(&self) -> u32 {
+        match self {
+            ReceiptWidth::Mm58 => 384,
+            ReceiptWidth::Mm80 => 576,
+        }
+    }
+}
+
+/// Paper cut after the receipt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptCut {
+    None,
+    /// Leaves a small tab so the receipt doesn't fall
+    #[default]
+    Partial,
+    Full,
+}
+
+/// Where the ESC/POS bytes are written
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReceiptDestination {
+    /// Character device or port, e.g. `/dev/usb/lp0` or `\\.\COM3`
+    Device { path: PathBuf },
+    /// Network receipt printer listening for raw jobs
+    Socket {
+        host: String,
+        #[serde(default = "default_raw_port")]
+        port: u16,
+    },
+}
+
+fn default_raw_port() -> u16 {
+    RAW_PORT
+}
+
+impl fmt::Display for ReceiptDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptDestination::Device { path } => write!(f, "{}", path.display()),
+            ReceiptDestination::Socket { host, port } => write!(f, "socket://{}:{}", host, port),
+        }
+    }
+}
+
+/// Receipt rendered to a monochrome raster and sent as ESC/POS
+#[derive(Debug, Deserialize)]
+pub struct ReceiptRequest {
+    pub html_content: String,
+    pub width: ReceiptWidth,
+    pub destination: ReceiptDestination,
+    #[serde(default)]
+    pub cut: ReceiptCut,
+    /// Pulse the cash drawer connected to the printer after printing
+    #[serde(default)]
+    pub open_cash_drawer: bool,
+    /// Maximum time to wait for fonts and images before rendering (ms)
+    #[serde(default = "default_render_timeout_ms")]
+    pub render_timeout_ms: u64,
+    /// Id carried by progress events and accepted by `cancel_print` (generated if None)
+    #[serde(default)]
+    pub task_id: Option<String>,
+}
+
+fn default_render_timeout_ms() -> u64 {
+    10_000
+}
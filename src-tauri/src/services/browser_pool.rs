@@ -0,0 +1,179 @@
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use headless_chrome::{Browser, LaunchOptions, Tab};
+use crate::models::print_error::PrintError;
+
+/// How long Chrome may sit without DevTools traffic before the connection is dropped.
+/// The pool keeps the browser for the whole app lifetime, so this is much longer
+/// than the headless_chrome default of 30 seconds.
+const BROWSER_IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum number of blank tabs kept open for reuse
+const MAX_IDLE_TABS: usize = 4;
+
+/// Headless Chrome instance shared by all print commands (stored in Tauri state)
+///
+/// Chrome is only launched on the first request. If the browser process dies it is
+/// relaunched transparently on the next request.
+pub struct BrowserPool {
+    state: Mutex<PoolState>,
+    /// Held while Chrome starts, so concurrent cold starts launch it only once.
+    /// Requests that find a live browser never wait on it.
+    launching: Mutex<()>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    browser: Option<Browser>,
+    /// Bumped on every launch, so tabs of a replaced browser are not pooled again
+    generation: u64,
+    idle_tabs: Vec<Arc<Tab>>,
+}
+
+impl BrowserPool {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(PoolState::default()),
+            launching: Mutex::new(()),
+        }
+    }
+
+    /// Borrows a tab from the pool, launching or restarting Chrome if needed
+    pub fn acquire_tab(&self) -> Result<PooledTab<'_>, PrintError> {
+        let (browser, generation) = match self.live_browser() {
+            Some(live) => live,
+            None => self.launch()?,
+        };
+
+        let idle_tab = {
+            let mut state = self.lock_state();
+            if state.generation == generation { state.idle_tabs.pop() } else { None }
+        };
+        let tab = match idle_tab {
+            Some(tab) => tab,
+            None => browser.new_tab().map_err(|e| PrintError::browser("new_tab", e))?,
+        };
+
+        Ok(PooledTab { pool: self, tab: Some(tab), generation })
+    }
+
+    /// Closes all tabs and terminates Chrome (called when the app exits)
+    pub fn shutdown(&self) {
+        let mut state = self.lock_state();
+
+        for tab in state.idle_tabs.drain(..) {
+            let _ = tab.close(false);
+        }
+
+        // Dropping the last handle closes the browser and kills the process
+        state.browser = None;
+    }
+
+    /// The running browser and its generation, forgetting it if it no longer answers
+    fn live_browser(&self) -> Option<(Browser, u64)> {
+        let (browser, generation) = {
+            let state = self.lock_state();
+            (state.browser.clone()?, state.generation)
+        };
+
+        // Asked outside the lock, so a hung browser doesn't block every other request
+        if browser.get_version().is_ok() {
+            return Some((browser, generation));
+        }
+
+        // A dead browser also invalidates every idle tab it owned
+        let mut state = self.lock_state();
+        if state.generation == generation {
+            state.browser = None;
+            state.idle_tabs.clear();
+        }
+        None
+    }
+
+    /// Starts Chrome without holding the pool lock (headless_chrome gives up after 30 s)
+    fn launch(&self) -> Result<(Browser, u64), PrintError> {
+        let _launching = self.launching.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Another request may have launched it while this one waited
+        if let Some(live) = self.live_browser() {
+            return Ok(live);
+        }
+
+        let browser = launch_browser()?;
+
+        let mut state = self.lock_state();
+        state.generation += 1;
+        state.browser = Some(browser.clone());
+        state.idle_tabs.clear();
+        Ok((browser, state.generation))
+    }
+
+    /// Returns a tab to the idle list once the caller is done with it
+    fn release(&self, tab: Arc<Tab>, generation: u64) {
+        // Clear the previous document so the next job starts from a blank page.
+        // A tab that cannot navigate anymore is broken and is simply discarded.
+        if tab.navigate_to("about:blank").is_err() {
+            return;
+        }
+
+        let mut state = self.lock_state();
+        let current = state.browser.is_some() && state.generation == generation;
+        if current && state.idle_tabs.len() < MAX_IDLE_TABS {
+            state.idle_tabs.push(tab);
+        } else {
+            let _ = tab.close(false);
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, PoolState> {
+        // A panic while holding the lock leaves the state usable, so ignore poisoning
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for BrowserPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tab borrowed from [`BrowserPool`], handed back to the pool on drop
+pub struct PooledTab<'a> {
+    pool: &'a BrowserPool,
+    tab: Option<Arc<Tab>>,
+    /// Generation of the browser the tab belongs to
+    generation: u64,
+}
+
+impl Deref for PooledTab<'_> {
+    type Target = Tab;
+
+    fn deref(&self) -> &Tab {
+        self.tab.as_ref().expect("pooled tab used after release")
+    }
+}
+
+impl Drop for PooledTab<'_> {
+    fn drop(&mut self) {
+        if let Some(tab) = self.tab.take() {
+            self.pool.release(tab, self.generation);
+        }
+    }
+}
+
+/// Starts a new headless Chrome process
+fn launch_browser() -> Result<Browser, PrintError> {
+    // Checked up front so a missing installation is reported distinctly from a crash
+    headless_chrome::browser::default_executable()
+        .map_err(|reason| PrintError::ChromeNotFound { reason })?;
+
+    let options = LaunchOptions::default_builder()
+        .headless(true)
+        .idle_browser_timeout(BROWSER_IDLE_TIMEOUT)
+        .build()
+        .map_err(|e| PrintError::browser("launch", e))?;
+
+    Browser::new(options)
+        .map_err(|e| PrintError::browser("launch", e))
+}
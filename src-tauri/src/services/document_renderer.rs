@@ -0,0 +1,486 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use headless_chrome::protocol::cdp::Emulation;
+use headless_chrome::types::PrintToPdfOptions;
+use serde_json::Value;
+use crate::models::document::{ConformanceReport, Document, OverflowReport, PageOverflow};
+use crate::models::print_config::{Orientation, PrintConfig};
+use crate::models::print_error::PrintError;
+use crate::models::print_progress::PrintStage;
+use crate::services::browser_pool::{BrowserPool, PooledTab};
+use crate::services::paginator::paginate;
+use crate::services::pdf;
+use crate::services::print_task::PrintTask;
+use crate::services::header_footer::{apply_templates, escape_html, split_margins};
+use crate::services::render_readiness::wait_until_render_ready;
+
+/// Millimetres per inch (Chrome page sizes and margins are in inches)
+const MM_PER_INCH: f32 = 25.4;
+
+/// CSS pixels per inch
+pub const CSS_PX_PER_INCH: f32 = 96.0;
+
+/// Pages printed per Chrome call; progress and cancel happen between batches
+const RENDER_BATCH_PAGES: usize = 10;
+
+/// Overflow below this many CSS pixels is layout rounding, not cut content
+const OVERFLOW_TOLERANCE_PX: f32 = 1.0;
+
+/// Script returning, for each page box, how many CSS pixels of content overflow it
+const MEASURE_OVERFLOW_SCRIPT: &str = r#"JSON.stringify(
+    Array.from(document.querySelectorAll('.print-page'))
+        .map((page) => Math.max(0, page.scrollHeight - page.clientHeight))
+)"#;
+
+/// PDF produced by the renderer
+pub struct RenderedPdf {
+    pub data: Vec<u8>,
+    /// Number of pages, known only after rendering for flowing documents
+    pub page_count: usize,
+    /// Pages whose content was cut by the page box
+    pub overflow: OverflowReport,
+    /// What the PDF/A conversion changed or couldn't fix, when asked for
+    pub conformance: Option<ConformanceReport>,
+}
+
+/// Renders documents through the shared Chrome headless instance
+///
+/// Every command builds its HTML and PDF options here, so all outputs share the
+/// same page box, margins and typography.
+pub struct DocumentRenderer<'a> {
+    pool: &'a BrowserPool,
+    task: Option<&'a PrintTask>,
+}
+
+impl<'a> DocumentRenderer<'a> {
+    pub fn new(pool: &'a BrowserPool) -> Self {
+        Self { pool, task: None }
+    }
+
+    /// Reports each stage to `task` and stops between stages once it is canceled
+    pub fn with_task(mut self, task: &'a PrintTask) -> Self {
+        self.task = Some(task);
+        self
+    }
+
+    /// Renders the document to PDF bytes
+    ///
+    /// Flowing documents are split into pages first. Overflowing pages are measured
+    /// next; with `fail_on_overflow` set they abort the render instead of being silently cut.
+    pub fn render_pdf(&self, document: &Document) -> Result<RenderedPdf, PrintError> {
+        if document.flowing {
+            return self.render_pdf(&self.paginate(document)?);
+        }
+
+        let tab = self.load(document)?;
+
+        let overflow = measure_overflow(&tab)?;
+        if document.config.fail_on_overflow && overflow.has_overflow() {
+            return Err(PrintError::ContentOverflow { pages: overflow.pages });
+        }
+
+        let outline = match document.outline {
+            Some(source) => pdf::outline::collect(&tab, source)?,
+            None => Vec::new(),
+        };
+
+        let data = self.print_in_batches(&tab, document)?;
+        let (data, conformance) = pdf::post_process(data, document, &outline)?;
+
+        Ok(RenderedPdf { data, page_count: document.pages.len(), overflow, conformance })
+    }
+
+    /// Prints the loaded pages a batch at a time and joins the batches into one PDF
+    ///
+    /// A `printToPDF` call reports nothing until it returns, so each batch is where
+    /// progress is reported and a canceled task stops.
+    fn print_in_batches(&self, tab: &PooledTab<'a>, document: &Document) -> Result<Vec<u8>, PrintError> {
+        let pages = document.pages.len();
+        let mut parts = Vec::new();
+        for first in (0..pages).step_by(RENDER_BATCH_PAGES) {
+            let last = (first + RENDER_BATCH_PAGES).min(pages);
+            self.advance(PrintStage::Rendering { page: first + 1, pages })?;
+
+            let options = PrintToPdfOptions {
+                page_ranges: Some(format!("{}-{}", first + 1, last)),
+                ..pdf_options(&document.config)
+            };
+            parts.push(tab.print_to_pdf(Some(options)).map_err(|e| PrintError::browser("print_to_pdf", e))?);
+        }
+        pdf::merge::merge(parts)
+    }
+
+    /// Splits the content of a flowing document into fixed pages
+    fn paginate(&self, document: &Document) -> Result<Document, PrintError> {
+        let paginated = paginate(self, &document.config, &document.pages.concat())?;
+        Ok(Document { pages: paginated.pages, flowing: false, ..document.clone() })
+    }
+
+    /// Loads the document and reports which pages overflow, without printing
+    pub fn check_overflow(&self, document: &Document) -> Result<OverflowReport, PrintError> {
+        let tab = self.load(document)?;
+        measure_overflow(&tab)
+    }
+
+    /// Loads the document into a pooled tab and waits until it is ready to print
+    pub fn load(&self, document: &Document) -> Result<PooledTab<'a>, PrintError> {
+        if document.pages.is_empty() {
+            return Err(PrintError::InvalidRequest {
+                reason: "document has no pages".to_string(),
+            });
+        }
+        document.config.validate()?;
+
+        let tab = self.open(&build_html(document))?;
+        wait_until_render_ready(&tab, &document.config)?;
+
+        Ok(tab)
+    }
+
+    /// Navigates a pooled tab to `html` with print media emulated
+    ///
+    /// Does not wait for fonts and images; callers follow up with the readiness check.
+    pub fn open(&self, html: &str) -> Result<PooledTab<'a>, PrintError> {
+        self.advance(PrintStage::StartingBrowser)?;
+        let tab = self.pool.acquire_tab()?;
+        self.advance(PrintStage::LoadingHtml)?;
+
+        // Data URI avoids file:// issues on Windows
+        let html_base64 = STANDARD.encode(html.as_bytes());
+        let data_uri = format!("data:text/html;base64,{}", html_base64);
+
+        tab.navigate_to(&data_uri)
+            .map_err(|e| PrintError::browser("navigate", e))?;
+        tab.wait_until_navigated()
+            .map_err(|e| PrintError::browser("navigate", e))?;
+
+        // Measurements and screenshots must see the same layout as the PDF
+        tab.call_method(Emulation::SetEmulatedMedia {
+            media: Some("print".to_string()),
+            features: None,
+        })
+        .map_err(|e| PrintError::browser("emulate_media", e))?;
+
+        Ok(tab)
+    }
+
+    /// Reports a stage to the task, if any (see [`PrintTask::advance`])
+    pub fn advance(&self, stage: PrintStage) -> Result<(), PrintError> {
+        match self.task {
+            Some(task) => task.advance(stage),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Builds the full HTML of the document, one fixed-size `.print-page` box per page
+pub fn build_html(document: &Document) -> String {
+    let config = &document.config;
+    let (page_width, page_height) = config.page_size_mm();
+
+    // Chrome owns the top/bottom margins when a header or footer is drawn
+    let (_, (chrome_top, chrome_bottom)) = split_margins(config, 0);
+    let title = escape_html(document.title().unwrap_or_default());
+    // Chrome picks hyphenation and fonts by language
+    let lang = match document.metadata.as_ref().and_then(|metadata| metadata.language.as_deref()) {
+        Some(language) => format!(r#" lang="{}""#, escape_html(language)),
+        None => String::new(),
+    };
+
+    let pages_html: String = document
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(idx, content)| {
+            // Mirrored margins make the padding differ between odd and even pages
+            let (margins, _) = split_margins(config, idx);
+            format!(
+                r#"<div class="print-page" style="padding: {};">{}</div>"#,
+                margins.css_padding(),
+                content
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html{}>
+<head>
+    <meta charset="UTF-8">
+    <title>{}</title>
+    <style>
+        * {{
+            margin: 0;
+            padding: 0;
+            box-sizing: border-box;
+        }}
+
+        @page {{
+            size: {}mm {}mm;
+            margin: {}mm 0 {}mm 0;
+        }}
+
+        body {{
+            margin: 0;
+            padding: 0;
+            background: white;
+        }}
+
+        .print-page {{
+            width: {}mm;
+            height: {}mm;
+            background: white;
+            font-family: 'Times New Roman', serif;
+            font-size: 12pt;
+            line-height: 1.5;
+            overflow: hidden;
+            break-inside: avoid;
+            break-after: page;
+        }}
+
+        .print-page:last-child {{
+            break-after: auto;
+        }}
+
+        h1, h2, h3, h4, h5, h6 {{
+            margin-bottom: 0.5em;
+        }}
+
+        p {{
+            margin-bottom: 0.5em;
+        }}
+    </style>
+</head>
+<body>
+{}
+</body>
+</html>"#,
+        lang,
+        title,
+        page_width,
+        page_height,
+        chrome_top,
+        chrome_bottom,
+        page_width,
+        page_height - chrome_top - chrome_bottom,
+        pages_html
+    )
+}
+
+/// Measures each page box's scroll height against its fixed height
+fn measure_overflow(tab: &PooledTab<'_>) -> Result<OverflowReport, PrintError> {
+    let result = tab.evaluate(MEASURE_OVERFLOW_SCRIPT, false)
+        .map_err(|e| PrintError::browser("measure_overflow", e))?;
+
+    let overflow_px: Vec<f32> = match result.value {
+        Some(Value::String(json)) => serde_json::from_str(&json)
+            .map_err(|e| PrintError::browser("measure_overflow", e))?,
+        other => {
+            return Err(PrintError::browser("measure_overflow", format!("unexpected result {:?}", other)));
+        }
+    };
+
+    Ok(overflow_report(&overflow_px))
+}
+
+/// Converts millimetres to CSS pixels (96 per inch)
+pub fn mm_to_css_px(mm: f32) -> f32 {
+    mm / MM_PER_INCH * CSS_PX_PER_INCH
+}
+
+/// Converts overflowing CSS pixels per page into a report in mm
+fn overflow_report(overflow_px: &[f32]) -> OverflowReport {
+    let pages = overflow_px
+        .iter()
+        .enumerate()
+        .filter(|(_, px)| **px > OVERFLOW_TOLERANCE_PX)
+        .map(|(page_index, px)| PageOverflow {
+            page_index,
+            overflow_mm: px / CSS_PX_PER_INCH * MM_PER_INCH,
+        })
+        .collect();
+
+    OverflowReport { pages }
+}
+
+/// Builds Chrome's PDF options for the configuration
+pub fn pdf_options(config: &PrintConfig) -> PrintToPdfOptions {
+    let (page_width, page_height) = config.page_size_mm();
+
+    let mut options = PrintToPdfOptions {
+        landscape: Some(matches!(config.orientation, Orientation::Landscape)),
+        print_background: Some(true),
+        scale: Some(config.scale as f64),
+        paper_width: Some((page_width / MM_PER_INCH) as f64),
+        paper_height: Some((page_height / MM_PER_INCH) as f64),
+        // Margins are CSS padding in the HTML, not Chrome margins
+        margin_top: Some(0.0),
+        margin_bottom: Some(0.0),
+        margin_left: Some(0.0),
+        margin_right: Some(0.0),
+        prefer_css_page_size: Some(true),
+        ..Default::default()
+    };
+
+    // Headers/footers take the top/bottom margins back from the CSS padding
+    apply_templates(&mut options, config);
+
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pdf_options::PdfMetadata;
+    use crate::models::print_config::{Margins, PaperFormat, PrintRequest};
+
+    fn config() -> PrintConfig {
+        serde_json::from_value(serde_json::json!({
+            "format": "A4",
+            "orientation": "portrait",
+            "margins_mm": 20.0,
+            "scale": 1.0
+        }))
+        .unwrap()
+    }
+
+    fn document(pages: &[&str], config: PrintConfig) -> Document {
+        Document::new(pages.iter().map(|p| p.to_string()).collect(), config)
+    }
+
+    #[test]
+    fn html_has_one_fixed_page_box_per_page() {
+        let html = build_html(&document(&["<p>one</p>", "<p>two</p>"], config()));
+
+        assert_eq!(html.matches(r#"<div class="print-page""#).count(), 2);
+        assert!(html.contains("size: 210mm 297mm;"));
+        assert!(html.contains("height: 297mm;"));
+        assert!(html.contains("overflow: hidden;"));
+        assert!(html.contains(r#"style="padding: 20mm 20mm 20mm 20mm;"><p>one</p></div>"#));
+    }
+
+    #[test]
+    fn html_swaps_dimensions_in_landscape() {
+        let mut config = config();
+        config.orientation = Orientation::Landscape;
+        let html = build_html(&document(&["x"], config));
+
+        assert!(html.contains("size: 297mm 210mm;"));
+        assert!(html.contains("width: 297mm;"));
+    }
+
+    #[test]
+    fn html_mirrors_margins_on_even_pages() {
+        let mut config = config();
+        config.margins = Some(Margins {
+            inside: Some(25.0),
+            outside: Some(10.0),
+            ..Margins::uniform(15.0)
+        });
+        let html = build_html(&document(&["a", "b"], config));
+
+        assert!(html.contains(r#"padding: 15mm 10mm 15mm 25mm;">a"#));
+        assert!(html.contains(r#"padding: 15mm 25mm 15mm 10mm;">b"#));
+    }
+
+    #[test]
+    fn html_moves_vertical_margins_to_chrome_with_footer() {
+        let mut config = config();
+        config.footer_template = Some("Page {{page}} of {{pages}}".to_string());
+        config.document_title = Some("Q3 <Report>".to_string());
+        let html = build_html(&document(&["x"], config));
+
+        assert!(html.contains("margin: 20mm 0 20mm 0;"));
+        assert!(html.contains("height: 257mm;"));
+        assert!(html.contains(r#"padding: 0mm 20mm 0mm 20mm;"#));
+        assert!(html.contains("<title>Q3 &lt;Report&gt;</title>"));
+    }
+
+    #[test]
+    fn html_takes_title_and_language_from_metadata() {
+        let mut document = document(&["x"], config());
+        document.metadata = Some(PdfMetadata {
+            title: Some("Offer".to_string()),
+            language: Some("fr-CH".to_string()),
+            ..PdfMetadata::default()
+        });
+        let html = build_html(&document);
+
+        assert!(html.contains(r#"<html lang="fr-CH">"#));
+        assert!(html.contains("<title>Offer</title>"));
+    }
+
+    #[test]
+    fn overflow_report_converts_px_to_mm_and_skips_rounding() {
+        let report = overflow_report(&[0.0, 0.5, 96.0, 3.0]);
+
+        assert_eq!(report.pages.len(), 2);
+        assert_eq!(report.pages[0].page_index, 2);
+        assert!((report.pages[0].overflow_mm - 25.4).abs() < 1e-4);
+        assert_eq!(report.pages[1].page_index, 3);
+        assert!(!overflow_report(&[0.0, 1.0]).has_overflow());
+    }
+
+    #[test]
+    fn pdf_options_use_paper_size_in_inches() {
+        let mut config = config();
+        config.format = PaperFormat::Letter;
+        config.scale = 0.8;
+        let options = pdf_options(&config);
+
+        assert!((options.paper_width.unwrap() - 8.5).abs() < 1e-3);
+        assert!((options.paper_height.unwrap() - 11.0).abs() < 1e-3);
+        assert_eq!(options.margin_top, Some(0.0));
+        assert_eq!(options.display_header_footer, Some(false));
+        assert_eq!(options.landscape, Some(false));
+        assert!((options.scale.unwrap() - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pdf_options_enable_header_footer_templates() {
+        let mut config = config();
+        config.header_template = Some("{{title}} - {{date}}".to_string());
+        let options = pdf_options(&config);
+
+        assert_eq!(options.display_header_footer, Some(true));
+        assert!((options.margin_top.unwrap() - 20.0 / 25.4).abs() < 1e-6);
+        let header = options.header_template.unwrap();
+        assert!(header.contains(r#"<span class="title"></span> - <span class="date"></span>"#));
+        assert_eq!(options.footer_template.as_deref(), Some("<span></span>"));
+    }
+
+    #[test]
+    fn pdf_options_pad_templates_evenly_with_mirrored_margins() {
+        let mut config = config();
+        config.margins = Some(Margins {
+            inside: Some(25.0),
+            outside: Some(10.0),
+            ..Margins::uniform(15.0)
+        });
+        config.footer_template = Some("{{page}}".to_string());
+        let options = pdf_options(&config);
+
+        assert!(options.footer_template.unwrap().contains("padding: 0 25mm 0 25mm;"));
+    }
+
+    /// Needs a local Chrome or Chromium: `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn long_single_content_flows_across_pages() {
+        let request: PrintRequest = serde_json::from_value(serde_json::json!({
+            "config": { "format": "A6", "orientation": "portrait", "margins_mm": 10.0, "scale": 1.0 },
+            "html_content": "<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit.</p>".repeat(60),
+            "printer_id": null
+        }))
+        .unwrap();
+        let pool = BrowserPool::new();
+
+        let rendered = DocumentRenderer::new(&pool).render_pdf(&Document::from(&request)).unwrap();
+
+        let pdf = lopdf::Document::load_mem(&rendered.data).unwrap();
+        assert!(rendered.page_count > 1);
+        assert_eq!(pdf.get_pages().len(), rendered.page_count);
+        assert!(!rendered.overflow.has_overflow());
+        pool.shutdown();
+    }
+}
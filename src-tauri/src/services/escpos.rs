@@ -0,0 +1,157 @@
+//! ESC/POS encoding of receipts rendered as monochrome rasters
+
+use crate::models::receipt::ReceiptCut;
+
+/// Lines fed after the raster, so the end of the receipt clears the cutter
+const FEED_LINES_BEFORE_CUT: u8 = 4;
+
+/// Rows per `GS v 0` command; many printers cap the height of a single raster image
+const MAX_BAND_ROWS: u32 = 255;
+
+/// Luminance at or below which a pixel prints black
+const BLACK_THRESHOLD: i16 = 127;
+
+/// 8-bit grayscale image, 0 black to 255 white, row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrayImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// 1-bit image packed MSB first, eight pixels per byte, set bits print black
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub rows: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn bytes_per_row(&self) -> usize {
+        self.width.div_ceil(8) as usize
+    }
+}
+
+/// Reduces the image to black and white with Floyd–Steinberg error diffusion
+///
+/// Thermal heads can only burn a dot or not; diffusing the rounding error keeps
+/// greys, logos and photos recognisable instead of clipping them.
+pub fn dither(image: &GrayImage) -> Bitmap {
+    let width = image.width as usize;
+    let height = image.height as usize;
+    let bytes_per_row = width.div_ceil(8);
+    let mut rows = vec![0u8; bytes_per_row * height];
+
+    // Error carried into the current and the next row, one slot of padding per side
+    let mut current = vec![0i16; width + 2];
+    let mut next = vec![0i16; width + 2];
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = (image.pixels[y * width + x] as i16 + current[x + 1] / 16).clamp(0, 255);
+            let (black, error) = if value <= BLACK_THRESHOLD { (true, value) } else { (false, value - 255) };
+
+            if black {
+                rows[y * bytes_per_row + x / 8] |= 0x80 >> (x % 8);
+            }
+            current[x + 2] += error * 7;
+            next[x] += error * 3;
+            next[x + 1] += error * 5;
+            next[x + 2] += error;
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.iter_mut().for_each(|error| *error = 0);
+    }
+
+    Bitmap { width: image.width, height: image.height, rows }
+}
+
+/// Encodes the bitmap as a complete ESC/POS job: initialise, raster bands, feed,
+/// cut and optionally kick the cash drawer
+pub fn encode(bitmap: &Bitmap, cut: ReceiptCut, open_cash_drawer: bool) -> Vec<u8> {
+    let bytes_per_row = bitmap.bytes_per_row();
+    let mut job = Vec::with_capacity(bitmap.rows.len() + 64);
+
+    // ESC @: reset to defaults
+    job.extend_from_slice(&[0x1B, 0x40]);
+
+    // GS v 0 m xL xH yL yH d...: raster bit image, normal density
+    for band in bitmap.rows.chunks(bytes_per_row * MAX_BAND_ROWS as usize) {
+        let band_rows = (band.len() / bytes_per_row) as u16;
+        job.extend_from_slice(&[0x1D, 0x76, 0x30, 0x00]);
+        job.extend_from_slice(&(bytes_per_row as u16).to_le_bytes());
+        job.extend_from_slice(&band_rows.to_le_bytes());
+        job.extend_from_slice(band);
+    }
+
+    // ESC d n: print and feed n lines
+    job.extend_from_slice(&[0x1B, 0x64, FEED_LINES_BEFORE_CUT]);
+
+    // GS V m: full (0) or partial (1) cut
+    match cut {
+        ReceiptCut::None => {}
+        ReceiptCut::Full => job.extend_from_slice(&[0x1D, 0x56, 0x00]),
+        ReceiptCut::Partial => job.extend_from_slice(&[0x1D, 0x56, 0x01]),
+    }
+
+    // ESC p m t1 t2: pulse drawer pin 2 for 50 ms on, 500 ms off
+    if open_cash_drawer {
+        job.extend_from_slice(&[0x1B, 0x70, 0x00, 0x19, 0xFA]);
+    }
+
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(width: u32, height: u32, value: u8) -> GrayImage {
+        GrayImage { width, height, pixels: vec![value; (width * height) as usize] }
+    }
+
+    #[test]
+    fn dithering_keeps_black_white_and_mid_grey_density() {
+        assert!(dither(&gray(16, 4, 0)).rows.iter().all(|byte| *byte == 0xFF));
+        assert!(dither(&gray(16, 4, 255)).rows.iter().all(|byte| *byte == 0));
+
+        let bitmap = dither(&gray(64, 64, 128));
+        let black: u32 = bitmap.rows.iter().map(|byte| byte.count_ones()).sum();
+        let ratio = black as f32 / (64.0 * 64.0);
+        assert!((0.45..=0.55).contains(&ratio), "{} of the pixels are black", ratio);
+    }
+
+    #[test]
+    fn rows_are_padded_to_whole_bytes() {
+        let mut image = gray(10, 1, 255);
+        image.pixels[9] = 0;
+
+        let bitmap = dither(&image);
+
+        assert_eq!(bitmap.bytes_per_row(), 2);
+        assert_eq!(bitmap.rows, vec![0x00, 0x40]);
+    }
+
+    #[test]
+    fn encodes_raster_bands_cut_and_drawer_kick() {
+        let bitmap = dither(&gray(384, 300, 0));
+
+        let job = encode(&bitmap, ReceiptCut::Partial, true);
+
+        assert_eq!(&job[..2], &[0x1B, 0x40]);
+        // 48 bytes per row, 255 rows in the first band and 45 in the second
+        assert_eq!(&job[2..10], &[0x1D, 0x76, 0x30, 0x00, 48, 0, 255, 0]);
+        let second = 10 + 48 * 255;
+        assert_eq!(&job[second..second + 8], &[0x1D, 0x76, 0x30, 0x00, 48, 0, 45, 0]);
+        assert!(job.ends_with(&[0x1B, 0x64, 4, 0x1D, 0x56, 0x01, 0x1B, 0x70, 0x00, 0x19, 0xFA]));
+        assert_eq!(job.len(), 2 + 2 * 8 + 48 * 300 + 3 + 3 + 5);
+    }
+
+    #[test]
+    fn no_cut_leaves_only_the_feed() {
+        let job = encode(&dither(&gray(8, 1, 255)), ReceiptCut::None, false);
+
+        assert!(job.ends_with(&[0x00, 0x1B, 0x64, 4]));
+    }
+}
@@ -0,0 +1,86 @@
+use headless_chrome::types::PrintToPdfOptions;
+use crate::models::print_config::{Margins, PrintConfig};
+
+/// Placeholders accepted in header/footer templates and the Chrome classes they map to
+const TOKENS: &[(&str, &str)] = &[
+    ("{{page}}", r#"<span class="pageNumber"></span>"#),
+    ("{{pages}}", r#"<span class="totalPages"></span>"#),
+    ("{{date}}", r#"<span class="date"></span>"#),
+    ("{{title}}", r#"<span class="title"></span>"#),
+];
+
+/// Splits the configured margins between CSS padding and Chrome page margins
+///
+/// Chrome only draws header and footer templates inside its own page margins, so when
+/// either is set the top/bottom margins are handed to Chrome and the CSS padding keeps
+/// only left/right. Returns the CSS margins and the Chrome (top, bottom) margins in mm.
+pub fn split_margins(config: &PrintConfig, page_index: usize) -> (Margins, (f32, f32)) {
+    let margins = config.effective_margins().for_page(page_index);
+
+    if !config.has_header_footer() {
+        return (margins, (0.0, 0.0));
+    }
+
+    let css = Margins {
+        top: 0.0,
+        bottom: 0.0,
+        ..margins
+    };
+    (css, (margins.top, margins.bottom))
+}
+
+/// Enables Chrome's header/footer rendering with the configured templates
+///
+/// Chrome uses the same template on every page, so with mirrored margins it is padded
+/// by the wider of `inside` and `outside` on both sides to stay clear of the binding.
+pub fn apply_templates(pdf_options: &mut PrintToPdfOptions, config: &PrintConfig) {
+    if !config.has_header_footer() {
+        pdf_options.display_header_footer = Some(false);
+        return;
+    }
+
+    let (_, (top, bottom)) = split_margins(config, 0);
+    let mut margins = config.effective_margins();
+    if let (Some(inside), Some(outside)) = (margins.inside, margins.outside) {
+        margins.left = inside.max(outside);
+        margins.right = inside.max(outside);
+    }
+
+    pdf_options.display_header_footer = Some(true);
+    pdf_options.margin_top = Some((top / 25.4) as f64);
+    pdf_options.margin_bottom = Some((bottom / 25.4) as f64);
+    // An empty template still has to be given, otherwise Chrome prints its default one
+    pdf_options.header_template = Some(chrome_template(config.header_template.as_deref(), &margins));
+    pdf_options.footer_template = Some(chrome_template(config.footer_template.as_deref(), &margins));
+}
+
+/// Wraps a user template so it lines up with the page content
+///
+/// Chrome renders templates with a tiny default font and no horizontal padding.
+fn chrome_template(template: Option<&str>, margins: &Margins) -> String {
+    let Some(template) = template else {
+        return "<span></span>".to_string();
+    };
+
+    format!(
+        r#"<div style="width: 100%; padding: 0 {}mm 0 {}mm; font-family: 'Times New Roman', serif; font-size: 9pt;">{}</div>"#,
+        margins.right,
+        margins.left,
+        expand_tokens(template)
+    )
+}
+
+/// Replaces `{{page}}`, `{{pages}}`, `{{date}}` and `{{title}}` with Chrome's template classes
+pub fn expand_tokens(template: &str) -> String {
+    TOKENS
+        .iter()
+        .fold(template.to_string(), |html, (token, span)| html.replace(token, span))
+}
+
+/// Escapes text for use inside HTML (e.g. the document `<title>`)
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,481 @@
+//! Binary encoding of IPP/1.1 messages (RFC 8010)
+
+/// Delimiter tags separating attribute groups
+pub mod group_tag {
+    pub const OPERATION: u8 = 0x01;
+    pub const JOB: u8 = 0x02;
+    pub const END_OF_ATTRIBUTES: u8 = 0x03;
+    pub const PRINTER: u8 = 0x04;
+}
+
+/// Value tags
+mod value_tag {
+    pub const UNSUPPORTED: u8 = 0x10;
+    pub const UNKNOWN: u8 = 0x12;
+    pub const NO_VALUE: u8 = 0x13;
+    pub const INTEGER: u8 = 0x21;
+    pub const BOOLEAN: u8 = 0x22;
+    pub const ENUM: u8 = 0x23;
+    pub const OCTET_STRING: u8 = 0x30;
+    pub const DATE_TIME: u8 = 0x31;
+    pub const RESOLUTION: u8 = 0x32;
+    pub const RANGE_OF_INTEGER: u8 = 0x33;
+    pub const BEG_COLLECTION: u8 = 0x34;
+    pub const END_COLLECTION: u8 = 0x37;
+    pub const TEXT: u8 = 0x41;
+    pub const NAME: u8 = 0x42;
+    pub const KEYWORD: u8 = 0x44;
+    pub const URI: u8 = 0x45;
+    pub const CHARSET: u8 = 0x47;
+    pub const NATURAL_LANGUAGE: u8 = 0x48;
+    pub const MIME_MEDIA_TYPE: u8 = 0x49;
+    pub const MEMBER_ATTR_NAME: u8 = 0x4A;
+}
+
+/// IPP operation ids
+pub mod operation {
+    pub const PRINT_JOB: u16 = 0x0002;
+    pub const CANCEL_JOB: u16 = 0x0008;
+    pub const GET_JOB_ATTRIBUTES: u16 = 0x0009;
+    pub const GET_JOBS: u16 = 0x000A;
+    pub const GET_PRINTER_ATTRIBUTES: u16 = 0x000B;
+    pub const CUPS_GET_DEFAULT: u16 = 0x4001;
+    pub const CUPS_GET_PRINTERS: u16 = 0x4002;
+}
+
+/// Single attribute value
+#[derive(Debug, Clone, PartialEq)]
+pub enum IppValue {
+    Integer(i32),
+    Boolean(bool),
+    Enum(i32),
+    Keyword(String),
+    Text(String),
+    Name(String),
+    Uri(String),
+    Charset(String),
+    NaturalLanguage(String),
+    MimeMediaType(String),
+    Range(i32, i32),
+    /// Cross-feed, feed, units (3 = dpi, 4 = dots per cm)
+    Resolution(i32, i32, u8),
+    DateTime(Vec<u8>),
+    OctetString(Vec<u8>),
+    Collection(Vec<IppAttribute>),
+    /// unsupported, unknown or no-value
+    OutOfBand(u8),
+    /// Any other tag, kept raw
+    Other(u8, Vec<u8>),
+}
+
+impl IppValue {
+    /// String content of keyword/text/name/uri-like values
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            IppValue::Keyword(s)
+            | IppValue::Text(s)
+            | IppValue::Name(s)
+            | IppValue::Uri(s)
+            | IppValue::Charset(s)
+            | IppValue::NaturalLanguage(s)
+            | IppValue::MimeMediaType(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Numeric content of integer/enum values
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            IppValue::Integer(n) | IppValue::Enum(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            IppValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            IppValue::Integer(_) => value_tag::INTEGER,
+            IppValue::Boolean(_) => value_tag::BOOLEAN,
+            IppValue::Enum(_) => value_tag::ENUM,
+            IppValue::Keyword(_) => value_tag::KEYWORD,
+            IppValue::Text(_) => value_tag::TEXT,
+            IppValue::Name(_) => value_tag::NAME,
+            IppValue::Uri(_) => value_tag::URI,
+            IppValue::Charset(_) => value_tag::CHARSET,
+            IppValue::NaturalLanguage(_) => value_tag::NATURAL_LANGUAGE,
+            IppValue::MimeMediaType(_) => value_tag::MIME_MEDIA_TYPE,
+            IppValue::Range(..) => value_tag::RANGE_OF_INTEGER,
+            IppValue::Resolution(..) => value_tag::RESOLUTION,
+            IppValue::DateTime(_) => value_tag::DATE_TIME,
+            IppValue::OctetString(_) => value_tag::OCTET_STRING,
+            IppValue::Collection(_) => value_tag::BEG_COLLECTION,
+            IppValue::OutOfBand(tag) | IppValue::Other(tag, _) => *tag,
+        }
+    }
+
+    fn encode_bytes(&self) -> Vec<u8> {
+        match self {
+            IppValue::Integer(n) | IppValue::Enum(n) => n.to_be_bytes().to_vec(),
+            IppValue::Boolean(b) => vec![*b as u8],
+            IppValue::Range(low, high) => [low.to_be_bytes(), high.to_be_bytes()].concat(),
+            IppValue::Resolution(x, y, units) => {
+                let mut bytes = [x.to_be_bytes(), y.to_be_bytes()].concat();
+                bytes.push(*units);
+                bytes
+            }
+            IppValue::DateTime(bytes) | IppValue::OctetString(bytes) | IppValue::Other(_, bytes) => {
+                bytes.clone()
+            }
+            IppValue::Collection(_) | IppValue::OutOfBand(_) => Vec::new(),
+            text => text.as_str().unwrap_or_default().as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Named attribute with one or more values
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppAttribute {
+    pub name: String,
+    pub values: Vec<IppValue>,
+}
+
+impl IppAttribute {
+    pub fn new(name: &str, value: IppValue) -> Self {
+        IppAttribute {
+            name: name.to_string(),
+            values: vec![value],
+        }
+    }
+
+    /// First value of the attribute
+    pub fn value(&self) -> Option<&IppValue> {
+        self.values.first()
+    }
+}
+
+/// Attribute group (operation, job, printer, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppGroup {
+    pub tag: u8,
+    pub attributes: Vec<IppAttribute>,
+}
+
+impl IppGroup {
+    pub fn attribute(&self, name: &str) -> Option<&IppAttribute> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+}
+
+/// IPP request or response
+#[derive(Debug, Clone, PartialEq)]
+pub struct IppMessage {
+    pub version: (u8, u8),
+    /// Operation id in requests, status code in responses
+    pub code: u16,
+    pub request_id: u32,
+    pub groups: Vec<IppGroup>,
+    /// Document data following the attributes
+    pub data: Vec<u8>,
+}
+
+impl IppMessage {
+    /// New IPP/1.1 request with the mandatory charset and language attributes
+    pub fn request(operation: u16, request_id: u32) -> Self {
+        IppMessage {
+            version: (1, 1),
+            code: operation,
+            request_id,
+            groups: vec![IppGroup {
+                tag: group_tag::OPERATION,
+                attributes: vec![
+                    IppAttribute::new("attributes-charset", IppValue::Charset("utf-8".to_string())),
+                    IppAttribute::new(
+                        "attributes-natural-language",
+                        IppValue::NaturalLanguage("en".to_string()),
+                    ),
+                ],
+            }],
+            data: Vec::new(),
+        }
+    }
+
+    /// Appends an attribute to the group with `tag`, creating the group if needed
+    pub fn add(&mut self, tag: u8, attribute: IppAttribute) -> &mut Self {
+        match self.groups.iter_mut().find(|group| group.tag == tag) {
+            Some(group) => group.attributes.push(attribute),
+            None => self.groups.push(IppGroup {
+                tag,
+                attributes: vec![attribute],
+            }),
+        }
+        self
+    }
+
+    /// True for successful-ok* status codes
+    pub fn is_success(&self) -> bool {
+        self.code < 0x0100
+    }
+
+    /// Finds an attribute by name in the first group with `tag`
+    pub fn attribute(&self, tag: u8, name: &str) -> Option<&IppAttribute> {
+        self.groups
+            .iter()
+            .filter(|group| group.tag == tag)
+            .flat_map(|group| group.attributes.iter())
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Groups with `tag` (e.g. one job group per job in Get-Jobs)
+    pub fn groups_with_tag(&self, tag: u8) -> impl Iterator<Item = &IppGroup> {
+        self.groups.iter().filter(move |group| group.tag == tag)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![self.version.0, self.version.1];
+        out.extend_from_slice(&self.code.to_be_bytes());
+        out.extend_from_slice(&self.request_id.to_be_bytes());
+
+        for group in &self.groups {
+            out.push(group.tag);
+            for attribute in &group.attributes {
+                encode_attribute(&mut out, &attribute.name, &attribute.values);
+            }
+        }
+
+        out.push(group_tag::END_OF_ATTRIBUTES);
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = (reader.u8()?, reader.u8()?);
+        let code = reader.u16()?;
+        let request_id = reader.u32()?;
+
+        let mut groups: Vec<IppGroup> = Vec::new();
+        loop {
+            let tag = reader.u8()?;
+            match tag {
+                group_tag::END_OF_ATTRIBUTES => break,
+                0x00..=0x0F => groups.push(IppGroup { tag, attributes: Vec::new() }),
+                _ => {
+                    let group = groups.last_mut().ok_or("attribute outside of a group")?;
+                    let name = reader.string()?;
+                    let value = decode_value(&mut reader, tag)?;
+
+                    // An empty name adds another value to the previous attribute
+                    match group.attributes.last_mut() {
+                        Some(previous) if name.is_empty() => previous.values.push(value),
+                        _ if name.is_empty() => return Err("additional value without attribute".to_string()),
+                        _ => group.attributes.push(IppAttribute { name, values: vec![value] }),
+                    }
+                }
+            }
+        }
+
+        Ok(IppMessage {
+            version,
+            code,
+            request_id,
+            groups,
+            data: bytes[reader.pos..].to_vec(),
+        })
+    }
+}
+
+fn encode_attribute(out: &mut Vec<u8>, name: &str, values: &[IppValue]) {
+    for (index, value) in values.iter().enumerate() {
+        let name = if index == 0 { name } else { "" };
+        out.push(value.tag());
+        push_bytes(out, name.as_bytes());
+
+        if let IppValue::Collection(members) = value {
+            push_bytes(out, &[]);
+            for member in members {
+                out.push(value_tag::MEMBER_ATTR_NAME);
+                push_bytes(out, &[]);
+                push_bytes(out, member.name.as_bytes());
+                encode_attribute(out, "", &member.values);
+            }
+            out.push(value_tag::END_COLLECTION);
+            push_bytes(out, &[]);
+            push_bytes(out, &[]);
+        } else {
+            push_bytes(out, &value.encode_bytes());
+        }
+    }
+}
+
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn decode_value(reader: &mut Reader<'_>, tag: u8) -> Result<IppValue, String> {
+    if tag == value_tag::BEG_COLLECTION {
+        reader.bytes_field()?;
+        return decode_collection(reader);
+    }
+
+    let bytes = reader.bytes_field()?;
+    let text = || String::from_utf8_lossy(bytes).to_string();
+    let int_at = |offset: usize| -> Result<i32, String> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| format!("value too short for tag 0x{:02x}", tag))
+    };
+
+    Ok(match tag {
+        value_tag::UNSUPPORTED | value_tag::UNKNOWN | value_tag::NO_VALUE => IppValue::OutOfBand(tag),
+        value_tag::INTEGER => IppValue::Integer(int_at(0)?),
+        value_tag::ENUM => IppValue::Enum(int_at(0)?),
+        value_tag::BOOLEAN => IppValue::Boolean(bytes.first().is_some_and(|b| *b != 0)),
+        value_tag::RANGE_OF_INTEGER => IppValue::Range(int_at(0)?, int_at(4)?),
+        value_tag::RESOLUTION => IppValue::Resolution(int_at(0)?, int_at(4)?, bytes.get(8).copied().unwrap_or(3)),
+        value_tag::DATE_TIME => IppValue::DateTime(bytes.to_vec()),
+        value_tag::OCTET_STRING => IppValue::OctetString(bytes.to_vec()),
+        value_tag::KEYWORD => IppValue::Keyword(text()),
+        value_tag::TEXT => IppValue::Text(text()),
+        value_tag::NAME => IppValue::Name(text()),
+        value_tag::URI => IppValue::Uri(text()),
+        value_tag::CHARSET => IppValue::Charset(text()),
+        value_tag::NATURAL_LANGUAGE => IppValue::NaturalLanguage(text()),
+        value_tag::MIME_MEDIA_TYPE => IppValue::MimeMediaType(text()),
+        _ => IppValue::Other(tag, bytes.to_vec()),
+    })
+}
+
+/// Reads collection members up to the matching endCollection
+fn decode_collection(reader: &mut Reader<'_>) -> Result<IppValue, String> {
+    let mut members: Vec<IppAttribute> = Vec::new();
+
+    loop {
+        let tag = reader.u8()?;
+        reader.bytes_field()?; // name, always empty inside collections
+
+        match tag {
+            value_tag::END_COLLECTION => {
+                reader.bytes_field()?;
+                return Ok(IppValue::Collection(members));
+            }
+            value_tag::MEMBER_ATTR_NAME => {
+                let name = String::from_utf8_lossy(reader.bytes_field()?).to_string();
+                members.push(IppAttribute { name, values: Vec::new() });
+            }
+            _ => {
+                let value = decode_value(reader, tag)?;
+                members
+                    .last_mut()
+                    .ok_or("collection value without member name")?
+                    .values
+                    .push(value);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or("truncated IPP message")?;
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn bytes_field(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        Ok(String::from_utf8_lossy(self.bytes_field()?).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_round_trips() {
+        let mut request = IppMessage::request(operation::PRINT_JOB, 7);
+        request
+            .add(group_tag::OPERATION, IppAttribute::new("printer-uri", IppValue::Uri("ipp://localhost/printers/x".into())))
+            .add(group_tag::JOB, IppAttribute::new("copies", IppValue::Integer(2)))
+            .add(group_tag::JOB, IppAttribute {
+                name: "page-ranges".into(),
+                values: vec![IppValue::Range(1, 3), IppValue::Range(5, 5)],
+            });
+        request.data = b"%PDF-1.7".to_vec();
+
+        let decoded = IppMessage::decode(&request.encode()).unwrap();
+
+        assert_eq!(decoded, request);
+    }
+
+    #[test]
+    fn encodes_header_and_first_attribute() {
+        let bytes = IppMessage::request(operation::PRINT_JOB, 1).encode();
+
+        assert_eq!(&bytes[..8], &[1, 1, 0x00, 0x02, 0, 0, 0, 1]);
+        assert_eq!(bytes[8], group_tag::OPERATION);
+        assert_eq!(bytes[9], value_tag::CHARSET);
+        assert_eq!(&bytes[10..12], &[0, 18]);
+        assert_eq!(&bytes[12..30], b"attributes-charset");
+        assert_eq!(*bytes.last().unwrap(), group_tag::END_OF_ATTRIBUTES);
+    }
+
+    #[test]
+    fn collections_round_trip() {
+        let mut response = IppMessage::request(0, 3);
+        response.add(group_tag::PRINTER, IppAttribute::new(
+            "media-col-default",
+            IppValue::Collection(vec![
+                IppAttribute::new("media-top-margin", IppValue::Integer(423)),
+                IppAttribute::new("media-type", IppValue::Keyword("stationery".into())),
+            ]),
+        ));
+
+        let decoded = IppMessage::decode(&response.encode()).unwrap();
+        let value = decoded.attribute(group_tag::PRINTER, "media-col-default").unwrap().value().unwrap();
+
+        assert_eq!(value, response.groups[1].attributes[0].value().unwrap());
+    }
+
+    #[test]
+    fn truncated_message_is_an_error() {
+        let bytes = IppMessage::request(operation::PRINT_JOB, 1).encode();
+
+        assert!(IppMessage::decode(&bytes[..bytes.len() - 5]).is_err());
+    }
+}
@@ -0,0 +1,722 @@
+//! Minimal IPP/1.1 client used to talk to CUPS (or any IPP printer) directly
+
+pub mod encoding;
+
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobState, JobStatus};
+use crate::services::tcp::{self, split_host_port};
+use encoding::{group_tag, operation, IppAttribute, IppGroup, IppMessage, IppValue};
+
+/// Default IPP port
+const IPP_PORT: u16 = 631;
+
+/// Local CUPS domain sockets, in lookup order
+#[cfg(unix)]
+const CUPS_SOCKETS: &[&str] = &["/run/cups/cups.sock", "/var/run/cups/cups.sock", "/private/var/run/cupsd"];
+
+/// Connect, read and write timeout for one request
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Request ids only need to be unique per connection; a counter keeps them readable in logs
+static NEXT_REQUEST_ID: AtomicU32 = AtomicU32::new(1);
+
+/// How the IPP server is reached
+#[derive(Debug, Clone, PartialEq)]
+enum Transport {
+    Tcp { host: String, port: u16 },
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// IPP client bound to one printer
+#[derive(Debug, Clone)]
+pub struct IppClient {
+    transport: Transport,
+    /// `printer-uri` operation attribute
+    printer_uri: String,
+    /// HTTP path requests are POSTed to
+    path: String,
+    /// Name used in errors (CUPS queue name or URI)
+    printer: String,
+    timeout: Duration,
+}
+
+impl IppClient {
+    /// Client for a CUPS queue name or a full `ipp://` printer URI
+    pub fn for_printer(printer: &str) -> Result<Self, PrintError> {
+        if printer.contains("://") {
+            Self::for_uri(printer)
+        } else {
+            Ok(Self::local_cups(printer))
+        }
+    }
+
+    /// Client for `ipp://host[:port]/path`
+    pub fn for_uri(uri: &str) -> Result<Self, PrintError> {
+        let invalid = |reason: &str| PrintError::InvalidRequest {
+            reason: format!("invalid printer URI {}: {}", uri, reason),
+        };
+
+        let rest = match uri.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("ipp") => rest,
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("ipps") => {
+                return Err(invalid("ipps (TLS) is not supported"));
+            }
+            _ => return Err(invalid("expected an ipp:// URI")),
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = split_host_port(authority, IPP_PORT).ok_or_else(|| invalid("bad host or port"))?;
+
+        Ok(IppClient {
+            transport: Transport::Tcp { host, port },
+            printer_uri: uri.to_string(),
+            path: path.to_string(),
+            printer: uri.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Client for the local CUPS server itself, for requests spanning all queues
+    pub fn cups_server() -> Self {
+        IppClient {
+            transport: cups_transport(std::env::var("CUPS_SERVER").ok().as_deref()),
+            printer_uri: "ipp://localhost/".to_string(),
+            path: "/".to_string(),
+            printer: "localhost".to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Client for a queue of the local CUPS server
+    ///
+    /// Honours `CUPS_SERVER` (host[:port] or socket path) like the CUPS tools, then
+    /// falls back to the local domain socket and finally to localhost:631.
+    pub fn local_cups(queue: &str) -> Self {
+        IppClient {
+            transport: cups_transport(std::env::var("CUPS_SERVER").ok().as_deref()),
+            printer_uri: format!("ipp://localhost/printers/{}", queue),
+            path: format!("/printers/{}", queue),
+            printer: queue.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Submits `document` with Print-Job and returns the job created by the printer
+    pub fn print_job(
+        &self,
+        job_name: &str,
+        document_format: &str,
+        document: Vec<u8>,
+        job_attributes: Vec<IppAttribute>,
+    ) -> Result<JobStatus, PrintError> {
+        let mut request = self.request(operation::PRINT_JOB);
+        request
+            .add(group_tag::OPERATION, IppAttribute::new("job-name", IppValue::Name(job_name.to_string())))
+            .add(
+                group_tag::OPERATION,
+                IppAttribute::new("document-format", IppValue::MimeMediaType(document_format.to_string())),
+            );
+        for attribute in job_attributes {
+            request.add(group_tag::JOB, attribute);
+        }
+        request.data = document;
+
+        let response = self.send(&request)?;
+        job_status(&response, None, &self.printer)
+    }
+
+    /// Reads the current state of a job with Get-Job-Attributes
+    pub fn get_job_attributes(&self, job_id: i32) -> Result<JobStatus, PrintError> {
+        let mut request = self.request(operation::GET_JOB_ATTRIBUTES);
+        request
+            .add(group_tag::OPERATION, IppAttribute::new("job-id", IppValue::Integer(job_id)))
+            .add(group_tag::OPERATION, requested_attributes(JOB_ATTRIBUTES));
+
+        let response = self.send(&request).map_err(|e| job_error(e, job_id))?;
+        job_status(&response, Some(job_id), &self.printer)
+    }
+
+    /// Lists jobs with Get-Jobs; `which_jobs` is "not-completed", "completed" or "all"
+    pub fn get_jobs(&self, which_jobs: &str) -> Result<Vec<JobStatus>, PrintError> {
+        let mut request = self.request(operation::GET_JOBS);
+        request
+            .add(group_tag::OPERATION, IppAttribute::new("which-jobs", IppValue::Keyword(which_jobs.to_string())))
+            .add(group_tag::OPERATION, requested_attributes(JOB_ATTRIBUTES));
+
+        let response = self.send(&request)?;
+        Ok(response
+            .groups_with_tag(group_tag::JOB)
+            .filter_map(|group| job_from_group(group, None, &self.printer))
+            .collect())
+    }
+
+    /// Reads printer attributes (capabilities, state, ...) with Get-Printer-Attributes
+    pub fn get_printer_attributes(&self, requested: &[&str]) -> Result<IppMessage, PrintError> {
+        let mut request = self.request(operation::GET_PRINTER_ATTRIBUTES);
+        request.add(group_tag::OPERATION, requested_attributes(requested));
+
+        self.send(&request)
+    }
+
+    /// Lists the queues of a CUPS server with CUPS-Get-Printers, one printer group each
+    pub fn cups_get_printers(&self, requested: &[&str]) -> Result<IppMessage, PrintError> {
+        let mut request = self.server_request(operation::CUPS_GET_PRINTERS);
+        request.add(group_tag::OPERATION, requested_attributes(requested));
+
+        self.send(&request)
+    }
+
+    /// Name of the CUPS server's default queue with CUPS-Get-Default, None if it has none
+    pub fn cups_get_default(&self) -> Result<Option<String>, PrintError> {
+        let mut request = self.server_request(operation::CUPS_GET_DEFAULT);
+        request.add(group_tag::OPERATION, requested_attributes(&["printer-name"]));
+
+        match self.send(&request) {
+            Ok(response) => Ok(response
+                .attribute(group_tag::PRINTER, "printer-name")
+                .and_then(|attribute| attribute.value())
+                .and_then(|value| value.as_str())
+                .map(|name| name.to_string())),
+            Err(PrintError::PrinterNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Cancels a pending, held or processing job
+    pub fn cancel_job(&self, job_id: i32) -> Result<(), PrintError> {
+        let mut request = self.request(operation::CANCEL_JOB);
+        request.add(group_tag::OPERATION, IppAttribute::new("job-id", IppValue::Integer(job_id)));
+
+        self.send(&request).map_err(|e| job_error(e, job_id))?;
+        Ok(())
+    }
+
+    /// New request with the printer and user operation attributes filled in
+    pub fn request(&self, operation: u16) -> IppMessage {
+        let mut request = IppMessage::request(operation, NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed));
+        request
+            .add(group_tag::OPERATION, IppAttribute::new("printer-uri", IppValue::Uri(self.printer_uri.clone())))
+            .add(
+                group_tag::OPERATION,
+                IppAttribute::new("requesting-user-name", IppValue::Name(requesting_user())),
+            );
+        request
+    }
+
+    /// New request addressed to the server rather than a printer (CUPS operations)
+    fn server_request(&self, operation: u16) -> IppMessage {
+        let mut request = IppMessage::request(operation, NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed));
+        request.add(
+            group_tag::OPERATION,
+            IppAttribute::new("requesting-user-name", IppValue::Name(requesting_user())),
+        );
+        request
+    }
+
+    /// Sends a request and returns the response, mapping IPP error statuses
+    pub fn send(&self, request: &IppMessage) -> Result<IppMessage, PrintError> {
+        let body = self.post(&request.encode())?;
+        let response = IppMessage::decode(&body).map_err(|e| PrintError::spooler("ipp", e))?;
+
+        if response.is_success() {
+            Ok(response)
+        } else {
+            Err(self.status_error(&response))
+        }
+    }
+
+    /// Maps an unsuccessful IPP status code to the error taxonomy
+    fn status_error(&self, response: &IppMessage) -> PrintError {
+        let printer = self.printer.clone();
+        let reason = response
+            .attribute(group_tag::OPERATION, "status-message")
+            .and_then(|attribute| attribute.value())
+            .and_then(|value| value.as_str())
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| format!("IPP status 0x{:04x}", response.code));
+
+        match response.code {
+            // client-error-not-found
+            0x0406 => PrintError::PrinterNotFound { printer },
+            // server-error-service-unavailable, -not-accepting-jobs, -busy
+            0x0502 | 0x0506 | 0x0507 => PrintError::PrinterUnavailable { printer, reason },
+            _ => PrintError::JobRejected { printer, reason },
+        }
+    }
+
+    /// POSTs an `application/ipp` body and returns the HTTP response body
+    fn post(&self, body: &[u8]) -> Result<Vec<u8>, PrintError> {
+        let host_header = match &self.transport {
+            Transport::Tcp { host, port } => {
+                // IPv6 literals are bracketed again in the Host header
+                let host = if host.contains(':') { format!("[{}]", host) } else { host.clone() };
+                if *port == IPP_PORT { host } else { format!("{}:{}", host, port) }
+            }
+            #[cfg(unix)]
+            Transport::Unix(_) => "localhost".to_string(),
+        };
+
+        let mut message = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            host_header,
+            body.len()
+        )
+        .into_bytes();
+        message.extend_from_slice(body);
+
+        let raw = match &self.transport {
+            Transport::Tcp { host, port } => {
+                let mut stream = tcp::connect(host, *port, self.timeout).map_err(|e| PrintError::spooler("ipp", e))?;
+                exchange(&mut stream, &message)
+            }
+            #[cfg(unix)]
+            Transport::Unix(path) => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path)
+                    .map_err(|e| PrintError::spooler("ipp", format!("{}: {}", path.display(), e)))?;
+                stream.set_read_timeout(Some(self.timeout)).ok();
+                stream.set_write_timeout(Some(self.timeout)).ok();
+                exchange(&mut stream, &message)
+            }
+        }
+        .map_err(|e| PrintError::spooler("ipp", e))?;
+
+        parse_http_response(&raw).map_err(|e| PrintError::spooler("ipp", e))
+    }
+}
+
+/// Picks the CUPS transport from `CUPS_SERVER` or the well-known socket paths
+fn cups_transport(cups_server: Option<&str>) -> Transport {
+    match cups_server.filter(|server| !server.is_empty()) {
+        #[cfg(unix)]
+        Some(server) if server.starts_with('/') => Transport::Unix(PathBuf::from(server)),
+        Some(server) => match split_host_port(server, IPP_PORT) {
+            Some((host, port)) => Transport::Tcp { host, port },
+            None => Transport::Tcp { host: server.to_string(), port: IPP_PORT },
+        },
+        None => {
+            #[cfg(unix)]
+            if let Some(socket) = CUPS_SOCKETS.iter().map(PathBuf::from).find(|path| path.exists()) {
+                return Transport::Unix(socket);
+            }
+            Transport::Tcp { host: "localhost".to_string(), port: IPP_PORT }
+        }
+    }
+}
+
+/// Writes the request and reads until the server closes the connection
+fn exchange(stream: &mut (impl Read + Write), message: &[u8]) -> std::io::Result<Vec<u8>> {
+    stream.write_all(message)?;
+    stream.flush()?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    Ok(raw)
+}
+
+/// Extracts the body of an HTTP/1.1 response, handling chunked encoding
+fn parse_http_response(raw: &[u8]) -> Result<Vec<u8>, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("incomplete HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let body = &raw[header_end + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(format!("HTTP error: {}", status_line));
+    }
+
+    let header = |name: &str| {
+        head.split("\r\n")
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+    };
+
+    if header("Transfer-Encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+        return decode_chunked(body);
+    }
+
+    match header("Content-Length").and_then(|value| value.parse::<usize>().ok()) {
+        Some(length) => body
+            .get(..length)
+            .map(|body| body.to_vec())
+            .ok_or_else(|| "truncated HTTP body".to_string()),
+        None => Ok(body.to_vec()),
+    }
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or("truncated chunk header")?;
+        let size_field = String::from_utf8_lossy(&body[..line_end]);
+        let size_hex = size_field.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16).map_err(|_| format!("bad chunk size {:?}", size_hex))?;
+
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+
+        out.extend_from_slice(body.get(..size).ok_or("truncated chunk")?);
+        body = body.get(size + 2..).ok_or("truncated chunk")?;
+    }
+}
+
+fn requested_attributes(names: &[&str]) -> IppAttribute {
+    IppAttribute {
+        name: "requested-attributes".to_string(),
+        values: names.iter().map(|name| IppValue::Keyword(name.to_string())).collect(),
+    }
+}
+
+/// In job operations, client-error-not-found refers to the job rather than the printer
+fn job_error(error: PrintError, job_id: i32) -> PrintError {
+    match error {
+        PrintError::PrinterNotFound { .. } => PrintError::JobNotFound { job_id: job_id.to_string() },
+        other => other,
+    }
+}
+
+/// Job attributes read for [`JobStatus`]
+const JOB_ATTRIBUTES: &[&str] = &[
+    "job-id",
+    "job-name",
+    "job-printer-uri",
+    "job-state",
+    "job-state-reasons",
+    "time-at-creation",
+    "time-at-completed",
+];
+
+/// Reads the single job of a Print-Job or Get-Job-Attributes response
+fn job_status(response: &IppMessage, job_id: Option<i32>, printer: &str) -> Result<JobStatus, PrintError> {
+    let empty = IppGroup { tag: group_tag::JOB, attributes: Vec::new() };
+    let group = response.groups_with_tag(group_tag::JOB).next().unwrap_or(&empty);
+
+    job_from_group(group, job_id, printer).ok_or_else(|| PrintError::spooler("ipp", "response has no job-id"))
+}
+
+/// Builds a job status from one job attribute group, None without a job id
+fn job_from_group(group: &IppGroup, job_id: Option<i32>, printer: &str) -> Option<JobStatus> {
+    let value = |name: &str| group.attribute(name).and_then(|attribute| attribute.value());
+
+    let job_id = value("job-id").and_then(|value| value.as_i32()).or(job_id)?;
+
+    // Print-Job responses may omit the state while the job is being queued
+    let state = value("job-state")
+        .and_then(|value| value.as_i32())
+        .and_then(JobState::from_ipp)
+        .unwrap_or(JobState::Pending);
+
+    let state_reasons = group
+        .attribute("job-state-reasons")
+        .map(|attribute| {
+            attribute
+                .values
+                .iter()
+                .filter_map(|value| value.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // CUPS job printer URIs end with /printers/<queue>
+    let printer = value("job-printer-uri")
+        .and_then(|value| value.as_str())
+        .and_then(|uri| uri.split_once("/printers/").map(|(_, queue)| queue.to_string()))
+        .unwrap_or_else(|| printer.to_string());
+
+    // CUPS reports Unix time; 0 means "not yet"
+    let time = |name: &str| {
+        value(name)
+            .and_then(|value| value.as_i32())
+            .filter(|seconds| *seconds > 0)
+            .map(i64::from)
+    };
+
+    Some(JobStatus {
+        job_id: job_id.to_string(),
+        printer,
+        name: value("job-name").and_then(|value| value.as_str()).map(|s| s.to_string()),
+        state,
+        state_reasons,
+        created_at: time("time-at-creation"),
+        completed_at: time("time-at-completed"),
+    })
+}
+
+/// Login name sent as the requesting user (also used for LPD jobs)
+pub fn requesting_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "anonymous".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serves one request on a local port and returns what the client sent
+    fn stand_in_server(respond: impl FnOnce(&IppMessage) -> Vec<u8> + Send + 'static) -> (u16, thread::JoinHandle<(String, IppMessage)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request = IppMessage::decode(&body).unwrap();
+
+            let mut stream = stream;
+            stream.write_all(&respond(&request)).unwrap();
+            (head, request)
+        });
+
+        (port, handle)
+    }
+
+    fn http_ok(body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn response_to(request: &IppMessage, status: u16) -> IppMessage {
+        let mut response = IppMessage::request(status, request.request_id);
+        response.code = status;
+        response
+    }
+
+    #[test]
+    fn parses_ipp_uris() {
+        let client = IppClient::for_uri("ipp://printhost:8631/ipp/print").unwrap();
+        assert_eq!(client.transport, Transport::Tcp { host: "printhost".into(), port: 8631 });
+        assert_eq!(client.path, "/ipp/print");
+
+        let client = IppClient::for_uri("ipp://[::1]/printers/office").unwrap();
+        assert_eq!(client.transport, Transport::Tcp { host: "::1".into(), port: 631 });
+
+        assert!(IppClient::for_uri("ipps://printhost/ipp/print").is_err());
+        assert!(IppClient::for_uri("http://printhost/").is_err());
+        assert!(IppClient::for_uri("ipp://:631/").is_err());
+    }
+
+    #[test]
+    fn queue_names_go_to_local_cups() {
+        let client = IppClient::for_printer("Office_Laser").unwrap();
+
+        assert_eq!(client.printer_uri, "ipp://localhost/printers/Office_Laser");
+        assert_eq!(client.path, "/printers/Office_Laser");
+        assert_eq!(
+            cups_transport(Some("cups.example:8631")),
+            Transport::Tcp { host: "cups.example".into(), port: 8631 }
+        );
+    }
+
+    #[test]
+    fn print_job_returns_job_id_and_state() {
+        let (port, server) = stand_in_server(|request| {
+            let mut response = response_to(request, 0x0000);
+            response
+                .add(group_tag::JOB, IppAttribute::new("job-id", IppValue::Integer(42)))
+                .add(group_tag::JOB, IppAttribute::new("job-state", IppValue::Enum(5)))
+                .add(group_tag::JOB, IppAttribute::new("job-state-reasons", IppValue::Keyword("job-printing".into())));
+            http_ok(&response.encode())
+        });
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/ipp/print", port)).unwrap();
+        let job = client
+            .print_job(
+                "report.pdf",
+                "application/pdf",
+                b"%PDF-1.7".to_vec(),
+                vec![IppAttribute::new("print-scaling", IppValue::Keyword("fit".into()))],
+            )
+            .unwrap();
+
+        assert_eq!(job.job_id, "42");
+        assert_eq!(job.state, JobState::Processing);
+        assert_eq!(job.state_reasons, vec!["job-printing".to_string()]);
+
+        let (head, request) = server.join().unwrap();
+        assert!(head.starts_with("POST /ipp/print HTTP/1.1\r\n"));
+        assert!(head.contains("Content-Type: application/ipp"));
+        assert_eq!(request.code, operation::PRINT_JOB);
+        assert_eq!(request.data, b"%PDF-1.7");
+        let uri = request.attribute(group_tag::OPERATION, "printer-uri").unwrap().value().unwrap();
+        assert_eq!(uri.as_str(), Some(format!("ipp://127.0.0.1:{}/ipp/print", port).as_str()));
+        let scaling = request.attribute(group_tag::JOB, "print-scaling").unwrap().value().unwrap();
+        assert_eq!(scaling.as_str(), Some("fit"));
+    }
+
+    #[test]
+    fn chunked_responses_are_decoded() {
+        let (port, server) = stand_in_server(|request| {
+            let mut response = response_to(request, 0x0000);
+            response.add(group_tag::JOB, IppAttribute::new("job-id", IppValue::Integer(7)));
+            response.add(group_tag::JOB, IppAttribute::new("job-state", IppValue::Enum(9)));
+            let body = response.encode();
+            let (first, second) = body.split_at(body.len() / 2);
+
+            let mut raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+            for chunk in [first, second] {
+                raw.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+                raw.extend_from_slice(chunk);
+                raw.extend_from_slice(b"\r\n");
+            }
+            raw.extend_from_slice(b"0\r\n\r\n");
+            raw
+        });
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/ipp/print", port)).unwrap();
+        let job = client.get_job_attributes(7).unwrap();
+
+        assert_eq!(job.job_id, "7");
+        assert_eq!(job.state, JobState::Completed);
+        assert_eq!(server.join().unwrap().1.code, operation::GET_JOB_ATTRIBUTES);
+    }
+
+    #[test]
+    fn get_jobs_reads_every_job_group() {
+        let (port, server) = stand_in_server(|request| {
+            let mut response = response_to(request, 0x0000);
+            for (id, state, queue) in [(12, 9, "Office"), (13, 5, "Lobby")] {
+                response.groups.push(IppGroup {
+                    tag: group_tag::JOB,
+                    attributes: vec![
+                        IppAttribute::new("job-id", IppValue::Integer(id)),
+                        IppAttribute::new("job-name", IppValue::Name(format!("report-{}", id))),
+                        IppAttribute::new("job-printer-uri", IppValue::Uri(format!("ipp://localhost/printers/{}", queue))),
+                        IppAttribute::new("job-state", IppValue::Enum(state)),
+                        IppAttribute::new("time-at-creation", IppValue::Integer(1_700_000_000)),
+                        IppAttribute::new("time-at-completed", IppValue::Integer(0)),
+                    ],
+                });
+            }
+            http_ok(&response.encode())
+        });
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/", port)).unwrap();
+        let jobs = client.get_jobs("all").unwrap();
+
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].job_id, "12");
+        assert_eq!(jobs[0].printer, "Office");
+        assert_eq!(jobs[0].name.as_deref(), Some("report-12"));
+        assert_eq!(jobs[0].state, JobState::Completed);
+        assert_eq!(jobs[0].created_at, Some(1_700_000_000));
+        assert_eq!(jobs[0].completed_at, None);
+        assert_eq!(jobs[1].state, JobState::Processing);
+
+        let request = server.join().unwrap().1;
+        assert_eq!(request.code, operation::GET_JOBS);
+        let which = request.attribute(group_tag::OPERATION, "which-jobs").unwrap().value().unwrap();
+        assert_eq!(which.as_str(), Some("all"));
+    }
+
+    #[test]
+    fn ipp_error_statuses_are_classified() {
+        let (port, server) = stand_in_server(|request| {
+            let mut response = response_to(request, 0x0506);
+            response.add(
+                group_tag::OPERATION,
+                IppAttribute::new("status-message", IppValue::Text("Printer is not accepting jobs".into())),
+            );
+            http_ok(&response.encode())
+        });
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/printers/office", port)).unwrap();
+        let error = client.print_job("x", "application/pdf", Vec::new(), Vec::new()).unwrap_err();
+
+        assert_eq!(error.code(), "printer_unavailable");
+        assert!(error.to_string().contains("not accepting jobs"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn unknown_jobs_are_job_not_found() {
+        let (port, server) = stand_in_server(|request| http_ok(&response_to(request, 0x0406).encode()));
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/printers/office", port)).unwrap();
+        let error = client.cancel_job(99).unwrap_err();
+
+        assert_eq!(error.code(), "job_not_found");
+        let request = server.join().unwrap().1;
+        assert_eq!(request.code, operation::CANCEL_JOB);
+        let job_id = request.attribute(group_tag::OPERATION, "job-id").unwrap().value().unwrap();
+        assert_eq!(job_id.as_i32(), Some(99));
+    }
+
+    #[test]
+    fn unreachable_server_is_spooler_unavailable() {
+        // Bind then drop to get a port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+        let client = IppClient::for_uri(&format!("ipp://127.0.0.1:{}/ipp/print", port)).unwrap();
+        let error = client.print_job("x", "application/pdf", Vec::new(), Vec::new()).unwrap_err();
+
+        assert_eq!(error.code(), "spooler_unavailable");
+    }
+
+    const MINIMAL_PDF: &[u8] = b"%PDF-1.4\n1 0 obj<</Type/Catalog/Pages 2 0 R>>endobj\n\
+2 0 obj<</Type/Pages/Kids[3 0 R]/Count 1>>endobj\n\
+3 0 obj<</Type/Page/Parent 2 0 R/MediaBox[0 0 595 842]>>endobj\n\
+trailer<</Root 1 0 R>>\n%%EOF\n";
+
+    /// Runs against a real IPP server, e.g. `ippeveprinter -p 8631 -f application/pdf Test`
+    /// with `IPP_TEST_PRINTER_URI=ipp://localhost:8631/ipp/print cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn prints_to_ipp_test_printer() {
+        let uri = std::env::var("IPP_TEST_PRINTER_URI").expect("IPP_TEST_PRINTER_URI is not set");
+        let client = IppClient::for_uri(&uri).unwrap();
+
+        let job = client
+            .print_job("ipp-client-test", "application/pdf", MINIMAL_PDF.to_vec(), Vec::new())
+            .unwrap();
+        let status = client.get_job_attributes(job.job_id.parse().unwrap()).unwrap();
+        assert_eq!(status.job_id, job.job_id);
+    }
+}
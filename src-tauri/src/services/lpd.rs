@@ -0,0 +1,222 @@
+//! Line Printer Daemon client (RFC 1179) for queues that are not set up in CUPS
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use crate::models::print_error::PrintError;
+use crate::services::ipp::requesting_user;
+use crate::services::tcp;
+
+/// Default LPD port
+pub const LPD_PORT: u16 = 515;
+
+/// RFC 1179 limits host names in control files to 31 characters
+const MAX_HOST_LEN: usize = 31;
+
+/// Job names are cut to the 99 characters most daemons accept
+const MAX_JOB_NAME_LEN: usize = 99;
+
+/// Offset added to the process id so consecutive jobs get distinct three-digit numbers
+static NEXT_JOB_NUMBER: AtomicU32 = AtomicU32::new(0);
+
+/// LPD queue on a remote host
+#[derive(Debug, Clone, PartialEq)]
+pub struct LpdQueue {
+    pub host: String,
+    pub port: u16,
+    pub queue: String,
+    pub timeout: Duration,
+}
+
+impl LpdQueue {
+    /// Sends `data` as one job printed `copies` times and returns the job number
+    ///
+    /// Every command and file transfer must be acknowledged with a zero byte; the
+    /// daemon refusing the queue itself means the printer is unavailable, refusing a
+    /// file means the job was rejected.
+    pub fn print(&self, printer: &str, job_name: &str, data: &[u8], copies: u32) -> Result<u16, PrintError> {
+        let unavailable = |reason: String| PrintError::PrinterUnavailable {
+            printer: printer.to_string(),
+            reason,
+        };
+        let rejected = |reason: String| PrintError::JobRejected {
+            printer: printer.to_string(),
+            reason,
+        };
+
+        let host = local_host_name();
+        let job_number = next_job_number();
+        let data_file = format!("dfA{:03}{}", job_number, host);
+        let control = control_file(&host, &requesting_user(), job_name, &data_file, copies);
+
+        let mut stream = tcp::connect(&self.host, self.port, self.timeout).map_err(unavailable)?;
+
+        // 02 queue LF: receive a printer job
+        command(&mut stream, format!("\x02{}\n", self.queue).as_bytes())
+            .map_err(|e| unavailable(format!("queue {}: {}", self.queue, e)))?;
+
+        // 02 count SP name LF: control file, then 03 count SP name LF: data file
+        let control_name = format!("cfA{:03}{}", job_number, host);
+        command(&mut stream, format!("\x02{} {}\n", control.len(), control_name).as_bytes())
+            .and_then(|_| transfer(&mut stream, control.as_bytes()))
+            .and_then(|_| command(&mut stream, format!("\x03{} {}\n", data.len(), data_file).as_bytes()))
+            .and_then(|_| transfer(&mut stream, data))
+            .map_err(rejected)?;
+
+        Ok(job_number)
+    }
+}
+
+/// Builds the control file: host, user, job name, then one `l` (print as-is) line per copy
+fn control_file(host: &str, user: &str, job_name: &str, data_file: &str, copies: u32) -> String {
+    let job_name: String = job_name
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_JOB_NAME_LEN)
+        .collect();
+
+    let mut control = format!("H{}\nP{}\nJ{}\nN{}\n", host, user, job_name, job_name);
+    for _ in 0..copies.max(1) {
+        control.push_str(&format!("l{}\n", data_file));
+    }
+    control.push_str(&format!("U{}\n", data_file));
+    control
+}
+
+/// Sends a command line and waits for the daemon's acknowledgement
+fn command(stream: &mut TcpStream, line: &[u8]) -> Result<(), String> {
+    stream.write_all(line).map_err(|e| e.to_string())?;
+    acknowledgement(stream)
+}
+
+/// Sends file contents followed by the terminating zero byte and waits for the acknowledgement
+fn transfer(stream: &mut TcpStream, contents: &[u8]) -> Result<(), String> {
+    stream
+        .write_all(contents)
+        .and_then(|_| stream.write_all(&[0]))
+        .map_err(|e| e.to_string())?;
+    acknowledgement(stream)
+}
+
+fn acknowledgement(stream: &mut TcpStream) -> Result<(), String> {
+    let mut ack = [0xFF; 1];
+    match stream.read(&mut ack) {
+        Ok(1) if ack[0] == 0 => Ok(()),
+        Ok(1) => Err(format!("refused (code {})", ack[0])),
+        Ok(_) => Err("connection closed without acknowledgement".to_string()),
+        Err(e) => Err(format!("no acknowledgement: {}", e)),
+    }
+}
+
+fn next_job_number() -> u16 {
+    let offset = NEXT_JOB_NUMBER.fetch_add(1, Ordering::Relaxed);
+    ((std::process::id().wrapping_add(offset)) % 1000) as u16
+}
+
+/// Host name for the control file, reduced to what RFC 1179 allows
+fn local_host_name() -> String {
+    let host: String = gethostname::gethostname()
+        .to_string_lossy()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.')
+        .take(MAX_HOST_LEN)
+        .collect();
+    if host.is_empty() { "localhost".to_string() } else { host }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Command lines with the file contents that followed them
+    type Session = Vec<(String, Vec<u8>)>;
+
+    /// Accepts one LPD session, acknowledging with `acks` in order, and returns what was received
+    fn stand_in_daemon(acks: Vec<u8>) -> (u16, thread::JoinHandle<Session>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut acks = acks.into_iter();
+            let mut received = Vec::new();
+
+            loop {
+                let mut line = Vec::new();
+                if reader.read_until(b'\n', &mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = String::from_utf8(line).unwrap();
+                let ack = acks.next().unwrap_or(0);
+                writer.write_all(&[ack]).unwrap();
+                if ack != 0 {
+                    received.push((line, Vec::new()));
+                    break;
+                }
+
+                // After the queue command, each subcommand announces <count> bytes plus a zero byte
+                let mut contents = Vec::new();
+                if !received.is_empty() {
+                    let count: usize = line[1..].split(' ').next().unwrap().parse().unwrap();
+                    contents = vec![0; count + 1];
+                    reader.read_exact(&mut contents).unwrap();
+                    contents.pop();
+                    writer.write_all(&[acks.next().unwrap_or(0)]).unwrap();
+                }
+                received.push((line, contents));
+            }
+            received
+        });
+
+        (port, handle)
+    }
+
+    fn queue(port: u16) -> LpdQueue {
+        LpdQueue { host: "127.0.0.1".to_string(), port, queue: "labels".to_string(), timeout: Duration::from_secs(5) }
+    }
+
+    #[test]
+    fn sends_control_and_data_files() {
+        let (port, daemon) = stand_in_daemon(Vec::new());
+
+        let job_number = queue(port).print("Dock", "Shipping\nlabel", b"%PDF-1.7", 2).unwrap();
+
+        let received = daemon.join().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(received[0].0, "\x02labels\n");
+
+        let (control_line, control) = &received[1];
+        assert!(control_line.starts_with(&format!("\x02{} cfA{:03}", control.len(), job_number)));
+        let control = String::from_utf8(control.clone()).unwrap();
+        assert!(control.contains("JShippinglabel\n"));
+        assert_eq!(control.matches("\nldfA").count(), 2);
+        assert!(control.contains(&format!("\nUdfA{:03}", job_number)));
+
+        let (data_line, data) = &received[2];
+        assert!(data_line.starts_with(&format!("\x038 dfA{:03}", job_number)));
+        assert_eq!(data, b"%PDF-1.7");
+    }
+
+    #[test]
+    fn refused_queue_is_unavailable() {
+        let (port, daemon) = stand_in_daemon(vec![1]);
+
+        let error = queue(port).print("Dock", "Label", b"data", 1).unwrap_err();
+
+        assert_eq!(error.code(), "printer_unavailable");
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn builds_rfc_1179_control_file() {
+        let control = control_file("pc1", "alice", "Report", "dfA042pc1", 1);
+
+        assert_eq!(control, "Hpc1\nPalice\nJReport\nNReport\nldfA042pc1\nUdfA042pc1\n");
+    }
+}
@@ -0,0 +1,16 @@
+pub mod browser_pool;
+pub mod document_renderer;
+pub mod escpos;
+pub mod header_footer;
+pub mod ipp;
+pub mod lpd;
+pub mod page_preview;
+pub mod paginator;
+pub mod pdf;
+pub mod print_backend;
+pub mod print_task;
+pub mod print_workers;
+pub mod raw_socket;
+pub mod receipt;
+pub mod render_readiness;
+pub mod tcp;
@@ -0,0 +1,85 @@
+use headless_chrome::protocol::cdp::Page;
+use serde_json::Value;
+use crate::models::document::{Document, PagePreview};
+use crate::models::print_error::PrintError;
+use crate::services::document_renderer::{DocumentRenderer, CSS_PX_PER_INCH};
+
+/// Accepted preview resolutions
+const MIN_DPI: f32 = 10.0;
+const MAX_DPI: f32 = 600.0;
+
+/// Script returning the document-relative box of every page as [x, y, width, height]
+const PAGE_BOXES_SCRIPT: &str = r#"JSON.stringify(
+    Array.from(document.querySelectorAll('.print-page')).map((page) => {
+        const rect = page.getBoundingClientRect();
+        return [rect.left + window.scrollX, rect.top + window.scrollY, rect.width, rect.height];
+    })
+)"#;
+
+/// Renders pages of the document to PNG at the given DPI
+///
+/// Uses the same HTML as the PDF, so the images match the printed page boxes.
+/// Chrome's running headers/footers are drawn by the PDF printer and do not appear.
+/// With `page_index` set only that page is captured, for fast refresh while editing.
+pub fn render_previews(
+    renderer: &DocumentRenderer<'_>,
+    document: &Document,
+    dpi: f32,
+    page_index: Option<usize>,
+) -> Result<Vec<PagePreview>, PrintError> {
+    if !(MIN_DPI..=MAX_DPI).contains(&dpi) {
+        return Err(PrintError::InvalidRequest {
+            reason: format!("DPI must be between {} and {}, got {}", MIN_DPI, MAX_DPI, dpi),
+        });
+    }
+    if let Some(index) = page_index {
+        if index >= document.pages.len() {
+            return Err(PrintError::InvalidRequest {
+                reason: format!("page {} does not exist (document has {} pages)", index, document.pages.len()),
+            });
+        }
+    }
+
+    let tab = renderer.load(document)?;
+
+    let result = tab.evaluate(PAGE_BOXES_SCRIPT, false)
+        .map_err(|e| PrintError::browser("preview", e))?;
+    let boxes: Vec<[f64; 4]> = match result.value {
+        Some(Value::String(json)) => serde_json::from_str(&json)
+            .map_err(|e| PrintError::browser("preview", e))?,
+        other => {
+            return Err(PrintError::browser("preview", format!("unexpected result {:?}", other)));
+        }
+    };
+
+    // A screenshot at scale 1 has 96 pixels per inch
+    let scale = (dpi / CSS_PX_PER_INCH) as f64;
+
+    boxes
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| page_index.is_none_or(|wanted| wanted == *index))
+        .map(|(index, &[x, y, width, height])| {
+            let png = tab
+                .call_method(Page::CaptureScreenshot {
+                    format: Some(Page::CaptureScreenshotFormatOption::Png),
+                    quality: None,
+                    clip: Some(Page::Viewport { x, y, width, height, scale }),
+                    from_surface: Some(true),
+                    // Pages below the window would otherwise come back blank
+                    capture_beyond_viewport: Some(true),
+                    optimize_for_speed: None,
+                })
+                .map_err(|e| PrintError::browser("screenshot", e))?
+                .data;
+
+            Ok(PagePreview {
+                page_index: index,
+                width_px: (width * scale).round() as u32,
+                height_px: (height * scale).round() as u32,
+                png_base64: png,
+            })
+        })
+        .collect()
+}
+
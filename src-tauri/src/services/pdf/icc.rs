@@ -0,0 +1,176 @@
+//! sRGB ICC profile (version 2, matrix/TRC) embedded as the PDF/A output intent
+
+/// Description of the profile, also the output condition of the intent
+pub const SRGB_DESCRIPTION: &str = "sRGB IEC61966-2.1";
+
+const COPYRIGHT: &str = "No copyright, use freely";
+
+/// Entries of the tone curve shared by the three channels
+const CURVE_ENTRIES: usize = 1024;
+
+/// Profile connection space illuminant
+const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+/// Media white point of sRGB displays
+const D65: [f64; 3] = [0.9505, 1.0, 1.0891];
+
+/// sRGB primaries adapted to D50 (Bradford)
+const RED: [f64; 3] = [0.4361, 0.2225, 0.0139];
+const GREEN: [f64; 3] = [0.3851, 0.7169, 0.0971];
+const BLUE: [f64; 3] = [0.1431, 0.0606, 0.7141];
+
+const HEADER_LEN: usize = 128;
+const TAG_ENTRY_LEN: usize = 12;
+
+/// Bytes of a display-class sRGB profile
+pub fn srgb_profile() -> Vec<u8> {
+    let blocks = [
+        text_description(SRGB_DESCRIPTION),
+        text(COPYRIGHT),
+        xyz(D65),
+        xyz(RED),
+        xyz(GREEN),
+        xyz(BLUE),
+        srgb_curve(),
+    ];
+    // Signature and the block it points at; the channels share one curve
+    let tags: [(&[u8; 4], usize); 9] = [
+        (b"desc", 0),
+        (b"cprt", 1),
+        (b"wtpt", 2),
+        (b"rXYZ", 3),
+        (b"gXYZ", 4),
+        (b"bXYZ", 5),
+        (b"rTRC", 6),
+        (b"gTRC", 6),
+        (b"bTRC", 6),
+    ];
+
+    let data_start = HEADER_LEN + 4 + tags.len() * TAG_ENTRY_LEN;
+    let mut offsets = Vec::with_capacity(blocks.len());
+    let mut data = Vec::new();
+    for block in &blocks {
+        offsets.push(data_start + data.len());
+        data.extend_from_slice(block);
+        // Tag data starts on 4-byte boundaries
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+
+    let mut profile = header(data_start + data.len());
+    profile.extend((tags.len() as u32).to_be_bytes());
+    for (signature, block) in tags {
+        profile.extend(signature);
+        profile.extend((offsets[block] as u32).to_be_bytes());
+        profile.extend((blocks[block].len() as u32).to_be_bytes());
+    }
+    profile.extend(data);
+    profile
+}
+
+fn header(size: usize) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend((size as u32).to_be_bytes());
+    header.extend([0; 4]); // preferred CMM
+    header.extend([2, 0x10, 0, 0]); // version 2.1
+    header.extend(b"mntr");
+    header.extend(b"RGB ");
+    header.extend(b"XYZ ");
+    for field in [2024u16, 1, 1, 0, 0, 0] {
+        header.extend(field.to_be_bytes());
+    }
+    header.extend(b"acsp");
+    header.extend([0; 24]); // platform, flags, manufacturer, model, attributes
+    header.extend(0u32.to_be_bytes()); // perceptual rendering intent
+    header.extend(s15_fixed16(D50));
+    header.resize(HEADER_LEN, 0);
+    header
+}
+
+/// `textDescriptionType` with an ASCII description and no localized variants
+fn text_description(description: &str) -> Vec<u8> {
+    let mut tag = type_signature(b"desc");
+    tag.extend((description.len() as u32 + 1).to_be_bytes());
+    tag.extend(description.as_bytes());
+    tag.push(0);
+    tag.extend([0; 4]); // Unicode language code
+    tag.extend([0; 4]); // Unicode count
+    tag.extend([0; 2]); // ScriptCode code
+    tag.push(0); // ScriptCode count
+    tag.extend([0; 67]);
+    tag
+}
+
+fn text(text: &str) -> Vec<u8> {
+    let mut tag = type_signature(b"text");
+    tag.extend(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+fn xyz(value: [f64; 3]) -> Vec<u8> {
+    let mut tag = type_signature(b"XYZ ");
+    tag.extend(s15_fixed16(value));
+    tag
+}
+
+/// sRGB transfer function: linear toe, then a 2.4 power curve
+fn srgb_curve() -> Vec<u8> {
+    let mut tag = type_signature(b"curv");
+    tag.extend((CURVE_ENTRIES as u32).to_be_bytes());
+    for index in 0..CURVE_ENTRIES {
+        let encoded = index as f64 / (CURVE_ENTRIES - 1) as f64;
+        let linear = if encoded <= 0.04045 {
+            encoded / 12.92
+        } else {
+            ((encoded + 0.055) / 1.055).powf(2.4)
+        };
+        tag.extend(((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+    tag
+}
+
+/// Type signature followed by the 4 reserved bytes
+fn type_signature(signature: &[u8; 4]) -> Vec<u8> {
+    let mut tag = signature.to_vec();
+    tag.extend([0; 4]);
+    tag
+}
+
+fn s15_fixed16(values: [f64; 3]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| ((value * 65536.0).round() as i32).to_be_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(data: &[u8], offset: usize) -> usize {
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn profile_header_and_tags_are_consistent() {
+        let profile = srgb_profile();
+
+        assert_eq!(u32_at(&profile, 0), profile.len());
+        assert_eq!(&profile[12..24], b"mntrRGB XYZ ");
+        assert_eq!(&profile[36..40], b"acsp");
+
+        let tag_count = u32_at(&profile, HEADER_LEN);
+        assert_eq!(tag_count, 9);
+        for tag in 0..tag_count {
+            let entry = HEADER_LEN + 4 + tag * TAG_ENTRY_LEN;
+            let (offset, size) = (u32_at(&profile, entry + 4), u32_at(&profile, entry + 8));
+            assert_eq!(offset % 4, 0);
+            assert!(offset + size <= profile.len());
+        }
+
+        // desc tag: type signature, then the ASCII description
+        let desc = u32_at(&profile, HEADER_LEN + 4 + 4);
+        assert_eq!(&profile[desc..desc + 4], b"desc");
+        assert_eq!(&profile[desc + 12..desc + 12 + SRGB_DESCRIPTION.len()], SRGB_DESCRIPTION.as_bytes());
+    }
+}
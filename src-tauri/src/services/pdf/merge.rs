@@ -0,0 +1,134 @@
+//! Joining the PDFs of separately printed page batches into one document
+
+use lopdf::{Document as PdfDocument, Object, ObjectId};
+use crate::models::print_error::PrintError;
+use crate::services::pdf::{load, save};
+
+/// Page attributes a page may inherit from its parent nodes in the page tree
+const INHERITED: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Appends the pages of every later part to the first one, in order
+///
+/// The first part keeps its catalog and Info dictionary; the page trees and catalogs
+/// of the others are dropped.
+pub fn merge(parts: Vec<Vec<u8>>) -> Result<Vec<u8>, PrintError> {
+    let mut parts = parts.into_iter();
+    let first = parts.next().ok_or_else(|| PrintError::pdf("merge", "no pages were rendered"))?;
+    let rest: Vec<Vec<u8>> = parts.collect();
+    if rest.is_empty() {
+        return Ok(first);
+    }
+
+    let mut merged = load(&first)?;
+    let pages_id = merged
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|e| PrintError::pdf("merge", e))?;
+
+    let mut appended: Vec<Object> = Vec::new();
+    for part in &rest {
+        let mut pdf = load(part)?;
+        pdf.renumber_objects_with(merged.max_id + 1);
+
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        for &page_id in &pages {
+            let inherited = inherited_attributes(&pdf, page_id);
+            let page = pdf.get_dictionary_mut(page_id).map_err(|e| PrintError::pdf("merge", e))?;
+            for (key, value) in inherited {
+                if !page.has(key) {
+                    page.set(key, value);
+                }
+            }
+            page.set("Parent", pages_id);
+        }
+
+        merged.objects.extend(pdf.objects);
+        merged.max_id = merged.objects.keys().map(|(id, _)| *id).max().unwrap_or(merged.max_id);
+        appended.extend(pages.into_iter().map(Object::Reference));
+    }
+
+    let root = merged.get_dictionary_mut(pages_id).map_err(|e| PrintError::pdf("merge", e))?;
+    let count = root.get(b"Count").and_then(Object::as_i64).unwrap_or(0) + appended.len() as i64;
+    let mut kids = root.get(b"Kids").and_then(Object::as_array).cloned().unwrap_or_default();
+    kids.extend(appended);
+    root.set("Kids", kids);
+    root.set("Count", count);
+
+    // The other parts' catalogs, page tree nodes and Info dictionaries are unreferenced now
+    merged.prune_objects();
+    save(&mut merged)
+}
+
+/// Attributes the page inherits from its ancestors without setting them itself
+fn inherited_attributes(pdf: &PdfDocument, page_id: ObjectId) -> Vec<(&'static [u8], Object)> {
+    let mut found: Vec<(&'static [u8], Object)> = Vec::new();
+    let mut node = pdf.get_dictionary(page_id).ok().and_then(|page| page.get(b"Parent").ok());
+    while let Some(Ok(parent)) = node.map(Object::as_reference) {
+        let Ok(dictionary) = pdf.get_dictionary(parent) else { break };
+        for &key in INHERITED {
+            if let Ok(value) = dictionary.get(key) {
+                if !found.iter().any(|(name, _)| *name == key) {
+                    found.push((key, value.clone()));
+                }
+            }
+        }
+        node = dictionary.get(b"Parent").ok();
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+    use crate::services::pdf::sample_pdf;
+
+    fn page_comments(data: &[u8]) -> Vec<String> {
+        let pdf = load(data).unwrap();
+        pdf.get_pages()
+            .into_values()
+            .map(|page_id| String::from_utf8(pdf.get_page_content(page_id).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn appends_pages_in_order() {
+        let merged = merge(vec![sample_pdf(2), sample_pdf(3)]).unwrap();
+
+        assert_eq!(page_comments(&merged), ["% page 1", "% page 2", "% page 1", "% page 2", "% page 3"]);
+        let pdf = load(&merged).unwrap();
+        let pages_id = pdf.catalog().unwrap().get(b"Pages").unwrap().as_reference().unwrap();
+        assert_eq!(pdf.get_dictionary(pages_id).unwrap().get(b"Count").unwrap().as_i64().unwrap(), 5);
+        // Only the first part's catalog and Info dictionary are kept
+        let catalogs = pdf.objects.values().filter(|o| o.type_name().ok() == Some(b"Catalog".as_slice())).count();
+        assert_eq!(catalogs, 1);
+    }
+
+    #[test]
+    fn single_part_is_returned_unchanged() {
+        let part = sample_pdf(1);
+
+        assert_eq!(merge(vec![part.clone()]).unwrap(), part);
+        assert_eq!(merge(Vec::new()).unwrap_err().code(), "pdf_processing_failed");
+    }
+
+    #[test]
+    fn attributes_inherited_from_the_page_tree_move_onto_the_page() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+        let page_id = *pdf.get_pages().get(&1).unwrap();
+        let pages_id = pdf.get_dictionary(page_id).unwrap().get(b"Parent").unwrap().as_reference().unwrap();
+        let media_box = pdf.get_dictionary_mut(page_id).unwrap().remove(b"MediaBox").unwrap();
+        let resources = pdf.add_object(dictionary! {});
+        let pages = pdf.get_dictionary_mut(pages_id).unwrap();
+        pages.set("MediaBox", media_box.clone());
+        pages.set("Resources", resources);
+        let part = save(&mut pdf).unwrap();
+
+        let merged = load(&merge(vec![sample_pdf(1), part]).unwrap()).unwrap();
+        let appended = merged.get_dictionary(*merged.get_pages().get(&2).unwrap()).unwrap();
+
+        assert_eq!(appended.get(b"MediaBox").unwrap(), &media_box);
+        assert!(appended.get(b"Resources").unwrap().as_reference().is_ok());
+    }
+}
@@ -0,0 +1,340 @@
+//! Info dictionary, XMP metadata stream and catalog language
+
+use lopdf::{decode_text_string, dictionary, text_string, Dictionary, Document as PdfDocument, Object, ObjectId, Stream};
+use crate::models::pdf_options::{PdfConformance, PdfMetadata};
+use crate::models::print_error::PrintError;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Writes `metadata` into the Info dictionary, an XMP stream and the catalog `/Lang`
+///
+/// Chrome's `Title` (the HTML `<title>`), `Creator` and `Producer` are kept unless
+/// the metadata replaces them, and repeated in the XMP, so both places describe the
+/// document the same way; empty values are left out of both. `now` (Unix seconds) is
+/// the creation date unless the metadata fixes one. `conformance` adds the PDF/A
+/// identification to the XMP; only pass it once the file actually conforms.
+pub fn apply(
+    pdf: &mut PdfDocument,
+    metadata: &PdfMetadata,
+    now: i64,
+    conformance: Option<PdfConformance>,
+) -> Result<(), PrintError> {
+    let created_at = metadata.created_at.unwrap_or(now);
+    let modified_at = metadata.modified_at.unwrap_or(created_at);
+
+    let mut info = existing_info(pdf);
+    let mut set_text = |key: &str, value: Option<&str>| {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            info.set(key, text_string(value));
+        }
+    };
+    let keywords: Vec<&str> = metadata
+        .keywords
+        .iter()
+        .map(String::as_str)
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    set_text("Title", metadata.title.as_deref());
+    set_text("Author", metadata.author.as_deref());
+    set_text("Subject", metadata.subject.as_deref());
+    set_text("Keywords", Some(&keywords.join(", ")));
+    info.set("CreationDate", Object::string_literal(pdf_date(created_at)));
+    info.set("ModDate", Object::string_literal(pdf_date(modified_at)));
+
+    let read_text = |key: &str| info.get(key.as_bytes()).ok().and_then(|value| decode_text_string(value).ok());
+    let xmp = xmp_packet(&XmpProperties {
+        title: read_text("Title"),
+        author: read_text("Author"),
+        subject: read_text("Subject"),
+        keywords,
+        language: metadata.language.as_deref().filter(|language| !language.is_empty()),
+        producer: read_text("Producer"),
+        creator_tool: read_text("Creator"),
+        created_at,
+        modified_at,
+        conformance,
+    });
+
+    let info_id = replace_or_add(pdf, pdf.trailer.get(b"Info").and_then(Object::as_reference).ok(), info.into());
+    pdf.trailer.set("Info", info_id);
+
+    // Kept uncompressed so indexers can read it without inflating streams
+    let mut stream = Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, xmp.into_bytes());
+    stream.allows_compression = false;
+    let existing = pdf.catalog().ok().and_then(|catalog| catalog.get(b"Metadata").and_then(Object::as_reference).ok());
+    let metadata_id = replace_or_add(pdf, existing, stream.into());
+
+    let catalog = pdf.catalog_mut().map_err(|e| PrintError::pdf("metadata", e))?;
+    catalog.set("Metadata", metadata_id);
+    if let Some(language) = metadata.language.as_deref().filter(|language| !language.is_empty()) {
+        catalog.set("Lang", text_string(language));
+    }
+
+    Ok(())
+}
+
+/// Stores `object` under `existing` if given, so the old one doesn't linger unreferenced
+fn replace_or_add(pdf: &mut PdfDocument, existing: Option<ObjectId>, object: Object) -> ObjectId {
+    match existing {
+        Some(id) => {
+            pdf.objects.insert(id, object);
+            id
+        }
+        None => pdf.add_object(object),
+    }
+}
+
+/// Copy of the current Info dictionary, or an empty one
+fn existing_info(pdf: &PdfDocument) -> Dictionary {
+    pdf.trailer
+        .get(b"Info")
+        .and_then(|info| pdf.dereference(info))
+        .and_then(|(_, info)| info.as_dict())
+        .cloned()
+        .unwrap_or_default()
+}
+
+struct XmpProperties<'a> {
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    keywords: Vec<&'a str>,
+    language: Option<&'a str>,
+    producer: Option<String>,
+    creator_tool: Option<String>,
+    created_at: i64,
+    modified_at: i64,
+    conformance: Option<PdfConformance>,
+}
+
+/// Dublin Core, XMP basic and Adobe PDF properties mirroring the Info dictionary,
+/// plus the PDF/A identification when claimed
+fn xmp_packet(properties: &XmpProperties<'_>) -> String {
+    let mut fields = String::from("   <dc:format>application/pdf</dc:format>\n");
+
+    let mut push = |field: String| {
+        fields.push_str("   ");
+        fields.push_str(&field);
+        fields.push('\n');
+    };
+    if let Some(title) = properties.title.as_deref().filter(|title| !title.is_empty()) {
+        push(format!("<dc:title>{}</dc:title>", alt(title)));
+    }
+    if let Some(author) = &properties.author {
+        push(format!("<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>", escape_xml(author)));
+    }
+    if let Some(subject) = &properties.subject {
+        push(format!("<dc:description>{}</dc:description>", alt(subject)));
+    }
+    if !properties.keywords.is_empty() {
+        let items: String = properties
+            .keywords
+            .iter()
+            .map(|keyword| format!("<rdf:li>{}</rdf:li>", escape_xml(keyword)))
+            .collect();
+        push(format!("<dc:subject><rdf:Bag>{}</rdf:Bag></dc:subject>", items));
+        push(format!("<pdf:Keywords>{}</pdf:Keywords>", escape_xml(&properties.keywords.join(", "))));
+    }
+    if let Some(language) = properties.language {
+        push(format!("<dc:language><rdf:Bag><rdf:li>{}</rdf:li></rdf:Bag></dc:language>", escape_xml(language)));
+    }
+    if let Some(producer) = &properties.producer {
+        push(format!("<pdf:Producer>{}</pdf:Producer>", escape_xml(producer)));
+    }
+    if let Some(creator_tool) = &properties.creator_tool {
+        push(format!("<xmp:CreatorTool>{}</xmp:CreatorTool>", escape_xml(creator_tool)));
+    }
+    push(format!("<xmp:CreateDate>{}</xmp:CreateDate>", xmp_date(properties.created_at)));
+    push(format!("<xmp:ModifyDate>{}</xmp:ModifyDate>", xmp_date(properties.modified_at)));
+    push(format!("<xmp:MetadataDate>{}</xmp:MetadataDate>", xmp_date(properties.modified_at)));
+    let mut namespaces = String::new();
+    if let Some(conformance) = properties.conformance {
+        namespaces.push_str("\n    xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\"");
+        push(format!("<pdfaid:part>{}</pdfaid:part>", conformance.part()));
+        push(format!("<pdfaid:conformance>{}</pdfaid:conformance>", conformance.level()));
+    }
+
+    format!(
+        r#"<?xpacket begin="{}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:pdf="http://ns.adobe.com/pdf/1.3/"{}>
+{}  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+        '\u{FEFF}', namespaces, fields
+    )
+}
+
+/// Language alternative with only the default entry
+fn alt(text: &str) -> String {
+    format!(r#"<rdf:Alt><rdf:li xml:lang="x-default">{}</rdf:li></rdf:Alt>"#, escape_xml(text))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// PDF date string in UTC, e.g. `D:20240301120000+00'00'`
+pub fn pdf_date(unix_seconds: i64) -> String {
+    let (year, month, day, hour, minute, second) = utc(unix_seconds);
+    format!("D:{:04}{:02}{:02}{:02}{:02}{:02}+00'00'", year, month, day, hour, minute, second)
+}
+
+/// XMP (ISO 8601) date in UTC, e.g. `2024-03-01T12:00:00+00:00`
+pub fn xmp_date(unix_seconds: i64) -> String {
+    let (year, month, day, hour, minute, second) = utc(unix_seconds);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}+00:00", year, month, day, hour, minute, second)
+}
+
+/// Splits Unix seconds into UTC calendar fields (proleptic Gregorian)
+fn utc(unix_seconds: i64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = unix_seconds.div_euclid(SECONDS_PER_DAY);
+    let seconds = unix_seconds.rem_euclid(SECONDS_PER_DAY);
+
+    // Days to civil date, counting in 400-year eras from 0000-03-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        (seconds / 3_600) as u32,
+        (seconds % 3_600 / 60) as u32,
+        (seconds % 60) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::pdf::{load, sample_pdf, save};
+
+    fn metadata() -> PdfMetadata {
+        PdfMetadata {
+            title: Some("Quarterly <Report>".to_string()),
+            author: Some("Zoë Müller".to_string()),
+            subject: Some("Finance".to_string()),
+            keywords: vec!["q3".to_string(), "revenue".to_string()],
+            language: Some("de-CH".to_string()),
+            created_at: Some(1_709_294_400),
+            modified_at: None,
+        }
+    }
+
+    fn info_text(pdf: &PdfDocument, key: &str) -> String {
+        let (_, info) = pdf.dereference(pdf.trailer.get(b"Info").unwrap()).unwrap();
+        decode_text_string(info.as_dict().unwrap().get(key.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn writes_info_dictionary_xmp_and_language() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+
+        apply(&mut pdf, &metadata(), 0, None).unwrap();
+        let pdf = load(&save(&mut pdf).unwrap()).unwrap();
+
+        assert_eq!(info_text(&pdf, "Title"), "Quarterly <Report>");
+        assert_eq!(info_text(&pdf, "Author"), "Zoë Müller");
+        assert_eq!(info_text(&pdf, "Keywords"), "q3, revenue");
+        assert_eq!(info_text(&pdf, "Producer"), "Skia/PDF m120");
+        assert_eq!(info_text(&pdf, "CreationDate"), "D:20240301120000+00'00'");
+        assert_eq!(info_text(&pdf, "ModDate"), "D:20240301120000+00'00'");
+
+        let catalog = pdf.catalog().unwrap();
+        assert_eq!(decode_text_string(catalog.get(b"Lang").unwrap()).unwrap(), "de-CH");
+        let xmp_id = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+        let xmp = String::from_utf8(pdf.get_object(xmp_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(xmp.contains(r#"<rdf:li xml:lang="x-default">Quarterly &lt;Report&gt;</rdf:li>"#));
+        assert!(xmp.contains("<rdf:li>Zoë Müller</rdf:li>"));
+        assert!(xmp.contains("<xmp:CreateDate>2024-03-01T12:00:00+00:00</xmp:CreateDate>"));
+        assert!(xmp.contains("<pdf:Producer>Skia/PDF m120</pdf:Producer>"));
+    }
+
+    #[test]
+    fn xmp_title_repeats_the_title_chrome_wrote() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+        let info_id = pdf.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        pdf.get_object_mut(info_id).unwrap().as_dict_mut().unwrap().set("Title", text_string("Offer 2024"));
+
+        apply(&mut pdf, &PdfMetadata { title: None, ..metadata() }, 0, None).unwrap();
+
+        assert_eq!(info_text(&pdf, "Title"), "Offer 2024");
+        let xmp_id = pdf.catalog().unwrap().get(b"Metadata").unwrap().as_reference().unwrap();
+        let xmp = String::from_utf8(pdf.get_object(xmp_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(xmp.contains(r#"<dc:title><rdf:Alt><rdf:li xml:lang="x-default">Offer 2024</rdf:li></rdf:Alt></dc:title>"#));
+    }
+
+    #[test]
+    fn empty_values_are_left_out() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+        let empty = PdfMetadata {
+            author: Some(String::new()),
+            subject: Some(String::new()),
+            keywords: vec![String::new(), "q3".to_string()],
+            language: Some(String::new()),
+            ..metadata()
+        };
+
+        apply(&mut pdf, &empty, 0, None).unwrap();
+
+        assert_eq!(info_text(&pdf, "Keywords"), "q3");
+        assert!(pdf.catalog().unwrap().get(b"Lang").is_err());
+        let xmp_id = pdf.catalog().unwrap().get(b"Metadata").unwrap().as_reference().unwrap();
+        let xmp = String::from_utf8(pdf.get_object(xmp_id).unwrap().as_stream().unwrap().content.clone()).unwrap();
+        assert!(!xmp.contains("<dc:creator>"));
+        assert!(!xmp.contains("<dc:description>"));
+        assert!(!xmp.contains("<dc:language>"));
+        assert!(xmp.contains("<dc:subject><rdf:Bag><rdf:li>q3</rdf:li></rdf:Bag></dc:subject>"));
+        assert!(xmp.contains("<pdf:Keywords>q3</pdf:Keywords>"));
+    }
+
+    #[test]
+    fn claims_pdf_a_only_when_asked() {
+        let xmp = |conformance| {
+            let mut pdf = load(&sample_pdf(1)).unwrap();
+            apply(&mut pdf, &metadata(), 0, conformance).unwrap();
+            let xmp_id = pdf.catalog().unwrap().get(b"Metadata").unwrap().as_reference().unwrap();
+            String::from_utf8(pdf.get_object(xmp_id).unwrap().as_stream().unwrap().content.clone()).unwrap()
+        };
+
+        let claimed = xmp(Some(PdfConformance::PdfA2b));
+        assert!(claimed.contains(r#"xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/">"#));
+        assert!(claimed.contains("<pdfaid:part>2</pdfaid:part>"));
+        assert!(claimed.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+        assert!(!xmp(None).contains("pdfaid"));
+    }
+
+    #[test]
+    fn fixed_dates_make_exports_identical() {
+        let export = |now| {
+            let mut pdf = load(&sample_pdf(2)).unwrap();
+            apply(&mut pdf, &metadata(), now, None).unwrap();
+            save(&mut pdf).unwrap()
+        };
+
+        assert_eq!(export(1), export(2));
+    }
+
+    #[test]
+    fn converts_unix_time_to_utc_dates() {
+        assert_eq!(pdf_date(0), "D:19700101000000+00'00'");
+        assert_eq!(xmp_date(951_782_400), "2000-02-29T00:00:00+00:00");
+        assert_eq!(xmp_date(1_735_689_599), "2024-12-31T23:59:59+00:00");
+    }
+}
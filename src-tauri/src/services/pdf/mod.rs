@@ -0,0 +1,126 @@
+//! Post-processing of the PDFs Chrome produces (metadata, outline, PDF/A, encryption, signing)
+
+pub mod icc;
+pub mod merge;
+pub mod metadata;
+pub mod outline;
+pub mod pdfa;
+pub mod protection;
+pub mod signature;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use lopdf::{Document as PdfDocument, Object, ObjectId};
+use crate::models::document::{ConformanceReport, Document};
+use crate::models::pdf_options::PdfMetadata;
+use crate::models::print_error::PrintError;
+use crate::services::pdf::outline::OutlineEntry;
+
+/// Applies what the document asks for beyond Chrome's output
+///
+/// `outline` holds the entries collected from the loaded page. Returns Chrome's
+/// bytes untouched when there is nothing to do, and the PDF/A report when the
+/// document asks for conformance.
+pub fn post_process(
+    data: Vec<u8>,
+    document: &Document,
+    outline: &[OutlineEntry],
+) -> Result<(Vec<u8>, Option<ConformanceReport>), PrintError> {
+    let untouched = document.metadata.is_none() && outline.is_empty() && document.conformance.is_none();
+    if untouched && document.protection.is_none() && document.signature.is_none() {
+        return Ok((data, None));
+    }
+
+    let now = unix_time();
+    let mut pdf = load(&data)?;
+    outline::apply(&mut pdf, outline, &document.config)?;
+
+    // PDF/A needs XMP metadata, so it is written even when none was asked for
+    let report = document.conformance.map(|conformance| pdfa::prepare(&mut pdf, conformance, &data));
+    let claim = report.as_ref().filter(|report| report.conformant).map(|report| report.conformance);
+    let metadata = document.metadata.clone().or_else(|| {
+        report.is_some().then(|| PdfMetadata { title: document.title().map(str::to_string), ..Default::default() })
+    });
+    if let Some(metadata) = &metadata {
+        metadata::apply(&mut pdf, metadata, now, claim)?;
+    }
+
+    if let Some(protection) = &document.protection {
+        protection::apply(&mut pdf, protection)?;
+    }
+    let data = match &document.signature {
+        Some(signature) => signature::sign(&mut pdf, signature, now)?,
+        None => save(&mut pdf)?,
+    };
+    Ok((data, report))
+}
+
+pub fn load(data: &[u8]) -> Result<PdfDocument, PrintError> {
+    PdfDocument::load_mem(data).map_err(|e| PrintError::pdf("load", e))
+}
+
+pub fn save(pdf: &mut PdfDocument) -> Result<Vec<u8>, PrintError> {
+    let mut data = Vec::new();
+    pdf.save_to(&mut data).map_err(|e| PrintError::pdf("save", e))?;
+    Ok(data)
+}
+
+/// PDF points per millimetre
+pub const PT_PER_MM: f32 = 72.0 / 25.4;
+
+/// Height of A4 in points, for pages without a readable MediaBox
+const FALLBACK_PAGE_HEIGHT_PT: f32 = 842.0;
+
+/// Height of the page's MediaBox in points
+pub fn page_height(pdf: &PdfDocument, page_id: ObjectId) -> f32 {
+    let media_box = pdf
+        .get_dictionary(page_id)
+        .and_then(|page| page.get(b"MediaBox"))
+        .and_then(|media_box| media_box.as_array());
+
+    match media_box.map(|values| values.iter().map(Object::as_float).collect::<Result<Vec<_>, _>>()) {
+        Ok(Ok(values)) if values.len() == 4 => (values[3] - values[1]).abs(),
+        _ => FALLBACK_PAGE_HEIGHT_PT,
+    }
+}
+
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// PDF shaped like Chrome's output: `pages` empty A4 pages and an Info dictionary
+#[cfg(test)]
+pub fn sample_pdf(pages: usize) -> Vec<u8> {
+    use lopdf::{dictionary, Object, Stream};
+
+    let mut pdf = PdfDocument::with_version("1.4");
+    let pages_id = pdf.new_object_id();
+    let kids: Vec<Object> = (0..pages)
+        .map(|index| {
+            let content = pdf.add_object(Stream::new(dictionary! {}, format!("% page {}", index + 1).into_bytes()));
+            pdf.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                "Contents" => content,
+            })
+            .into()
+        })
+        .collect();
+    pdf.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => pages as i64 }),
+    );
+    let catalog_id = pdf.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    let info_id = pdf.add_object(dictionary! {
+        "Creator" => Object::string_literal("Chromium"),
+        "Producer" => Object::string_literal("Skia/PDF m120"),
+        "CreationDate" => Object::string_literal("D:20240101000000+00'00'"),
+    });
+    pdf.trailer.set("Root", catalog_id);
+    pdf.trailer.set("Info", info_id);
+
+    save(&mut pdf).unwrap()
+}
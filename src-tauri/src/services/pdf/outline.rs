@@ -0,0 +1,266 @@
+//! PDF outline (bookmarks) built from the headings or `data-bookmark` elements of the pages
+
+use headless_chrome::Tab;
+use lopdf::{dictionary, text_string, Dictionary, Document as PdfDocument, Object, ObjectId};
+use serde::Deserialize;
+use serde_json::Value;
+use crate::models::pdf_options::OutlineSource;
+use crate::models::print_config::PrintConfig;
+use crate::models::print_error::PrintError;
+use crate::services::header_footer::split_margins;
+use crate::services::pdf::{page_height, PT_PER_MM};
+
+/// PDF points per CSS pixel (72 vs 96 per inch)
+const PT_PER_CSS_PX: f32 = 0.75;
+
+/// One outline item as found in the rendered HTML
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OutlineEntry {
+    /// Nesting level, 1 being the top
+    pub level: u8,
+    pub title: String,
+    pub page_index: usize,
+    /// Distance from the top of the `.print-page` box in CSS pixels
+    pub top_px: f32,
+}
+
+/// Collects the outline entries of the loaded document, in document order
+pub fn collect(tab: &Tab, source: OutlineSource) -> Result<Vec<OutlineEntry>, PrintError> {
+    let result = tab.evaluate(&outline_script(source), false)
+        .map_err(|e| PrintError::browser("outline", e))?;
+
+    match result.value {
+        Some(Value::String(json)) => serde_json::from_str(&json).map_err(|e| PrintError::browser("outline", e)),
+        other => Err(PrintError::browser("outline", format!("unexpected result {:?}", other))),
+    }
+}
+
+/// Script returning the entries as JSON, measured against their page box
+fn outline_script(source: OutlineSource) -> String {
+    let (selector, explicit) = match source {
+        OutlineSource::Headings => ("h1, h2, h3, h4, h5, h6", false),
+        OutlineSource::Bookmarks => ("[data-bookmark]", true),
+    };
+
+    format!(
+        r#"JSON.stringify(
+    Array.from(document.querySelectorAll('.print-page')).flatMap((page, pageIndex) => {{
+        const pageTop = page.getBoundingClientRect().top;
+        return Array.from(page.querySelectorAll('{selector}')).map((element) => {{
+            const heading = /^H[1-6]$/.test(element.tagName) ? Number(element.tagName[1]) : 1;
+            const level = {explicit} ? (Number(element.dataset.bookmarkLevel) || 1) : heading;
+            const title = (({explicit} && element.dataset.bookmark) || element.textContent || '')
+                .replace(/\s+/g, ' ')
+                .trim();
+            return {{
+                level: Math.min(Math.max(level, 1), 255),
+                title,
+                page_index: pageIndex,
+                top_px: element.getBoundingClientRect().top - pageTop,
+            }};
+        }}).filter((entry) => entry.title);
+    }})
+)"#
+    )
+}
+
+/// Writes the entries as the document outline and opens the outline panel
+///
+/// Levels may skip (an `h3` right under an `h1` becomes its child). Each item jumps
+/// to the top of its element; items start collapsed below the first level.
+pub fn apply(pdf: &mut PdfDocument, entries: &[OutlineEntry], config: &PrintConfig) -> Result<(), PrintError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let pages = pdf.get_pages();
+    // Chrome's own top margin (headers/footers) sits above the page box
+    let (_, (chrome_top_mm, _)) = split_margins(config, 0);
+    let offset_pt = chrome_top_mm * PT_PER_MM;
+
+    let root_id = pdf.new_object_id();
+    let mut items: Vec<OutlineItem> = Vec::with_capacity(entries.len());
+    let mut open: Vec<usize> = Vec::new();
+
+    for entry in entries {
+        let Some(&page_id) = pages.get(&(entry.page_index as u32 + 1)) else {
+            return Err(PrintError::pdf("outline", format!("page {} does not exist", entry.page_index + 1)));
+        };
+
+        while open.last().is_some_and(|&index| items[index].level >= entry.level) {
+            open.pop();
+        }
+        let parent = open.last().copied();
+
+        let top = page_height(pdf, page_id) - offset_pt - entry.top_px * config.scale * PT_PER_CSS_PX;
+        items.push(OutlineItem {
+            id: pdf.new_object_id(),
+            level: entry.level,
+            parent,
+            children: Vec::new(),
+            dictionary: dictionary! {
+                "Title" => text_string(&entry.title),
+                "Dest" => vec![page_id.into(), "XYZ".into(), Object::Null, top.max(0.0).into(), Object::Null],
+            },
+        });
+        let index = items.len() - 1;
+        if let Some(parent) = parent {
+            items[parent].children.push(index);
+        }
+        open.push(index);
+    }
+
+    let top_level: Vec<usize> = (0..items.len()).filter(|&index| items[index].parent.is_none()).collect();
+    link_siblings(&mut items, &top_level, root_id);
+    for index in 0..items.len() {
+        let children = items[index].children.clone();
+        let id = items[index].id;
+        link_siblings(&mut items, &children, id);
+        if !children.is_empty() {
+            // Negative: collapsed, with this many items shown once expanded
+            items[index].dictionary.set("Count", -(children.len() as i64));
+        }
+    }
+
+    let root = dictionary! {
+        "Type" => "Outlines",
+        "First" => items[top_level[0]].id,
+        "Last" => items[*top_level.last().unwrap()].id,
+        "Count" => top_level.len() as i64,
+    };
+    pdf.objects.insert(root_id, Object::Dictionary(root));
+    for item in items {
+        pdf.objects.insert(item.id, Object::Dictionary(item.dictionary));
+    }
+
+    let catalog = pdf.catalog_mut().map_err(|e| PrintError::pdf("outline", e))?;
+    catalog.set("Outlines", root_id);
+    catalog.set("PageMode", "UseOutlines");
+    Ok(())
+}
+
+struct OutlineItem {
+    id: ObjectId,
+    level: u8,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    dictionary: Dictionary,
+}
+
+/// Sets Parent/Prev/Next on `siblings` and First/Last on their parent
+fn link_siblings(items: &mut [OutlineItem], siblings: &[usize], parent_id: ObjectId) {
+    for (position, &index) in siblings.iter().enumerate() {
+        let prev = position.checked_sub(1).map(|prev| items[siblings[prev]].id);
+        let next = siblings.get(position + 1).map(|&next| items[next].id);
+
+        let dictionary = &mut items[index].dictionary;
+        dictionary.set("Parent", parent_id);
+        if let Some(prev) = prev {
+            dictionary.set("Prev", prev);
+        }
+        if let Some(next) = next {
+            dictionary.set("Next", next);
+        }
+    }
+
+    if let (Some(&first), Some(&last)) = (siblings.first(), siblings.last()) {
+        let (first, last) = (items[first].id, items[last].id);
+        if let Some(parent) = items.iter_mut().find(|item| item.id == parent_id) {
+            parent.dictionary.set("First", first);
+            parent.dictionary.set("Last", last);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::decode_text_string;
+    use crate::services::pdf::{load, sample_pdf, save};
+
+    fn config() -> PrintConfig {
+        serde_json::from_value(serde_json::json!({
+            "format": "A4",
+            "orientation": "portrait",
+            "margins_mm": 20.0,
+            "scale": 1.0
+        }))
+        .unwrap()
+    }
+
+    fn entry(level: u8, title: &str, page_index: usize, top_px: f32) -> OutlineEntry {
+        OutlineEntry { level, title: title.to_string(), page_index, top_px }
+    }
+
+    fn title(item: &Dictionary) -> String {
+        decode_text_string(item.get(b"Title").unwrap()).unwrap()
+    }
+
+    fn linked<'a>(pdf: &'a PdfDocument, item: &Dictionary, key: &[u8]) -> &'a Dictionary {
+        pdf.get_dictionary(item.get(key).unwrap().as_reference().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn nests_headings_and_points_at_their_pages() {
+        let mut pdf = load(&sample_pdf(3)).unwrap();
+        let entries = [
+            entry(1, "Introduction", 0, 0.0),
+            entry(2, "Scope", 0, 400.0),
+            entry(3, "Out of scope", 1, 96.0),
+            entry(1, "Résumé", 2, 0.0),
+        ];
+
+        apply(&mut pdf, &entries, &config()).unwrap();
+        let pdf = load(&save(&mut pdf).unwrap()).unwrap();
+
+        let catalog = pdf.catalog().unwrap();
+        assert_eq!(catalog.get(b"PageMode").unwrap().as_name().unwrap(), b"UseOutlines");
+        let root = linked(&pdf, catalog, b"Outlines");
+        assert_eq!(root.get(b"Count").unwrap().as_i64().unwrap(), 2);
+
+        let introduction = linked(&pdf, root, b"First");
+        assert_eq!(title(introduction), "Introduction");
+        assert_eq!(title(linked(&pdf, introduction, b"Next")), "Résumé");
+        assert_eq!(title(linked(&pdf, root, b"Last")), "Résumé");
+
+        let scope = linked(&pdf, introduction, b"First");
+        assert_eq!(title(scope), "Scope");
+        let out_of_scope = linked(&pdf, scope, b"First");
+        assert_eq!(title(out_of_scope), "Out of scope");
+
+        // Dest: [page /XYZ null top null] on the second page, 1 inch (72 pt) down
+        let dest = out_of_scope.get(b"Dest").unwrap().as_array().unwrap();
+        assert_eq!(dest[0].as_reference().unwrap(), pdf.get_pages()[&2]);
+        assert!((dest[3].as_float().unwrap() - (842.0 - 72.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn header_margin_shifts_destinations_down() {
+        let mut config = config();
+        config.header_template = Some("{{title}}".to_string());
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+
+        apply(&mut pdf, &[entry(1, "Top", 0, 0.0)], &config).unwrap();
+
+        let root = linked(&pdf, pdf.catalog().unwrap(), b"Outlines");
+        let dest = linked(&pdf, root, b"First").get(b"Dest").unwrap().as_array().unwrap();
+        assert!((dest[3].as_float().unwrap() - (842.0 - 20.0 * PT_PER_MM)).abs() < 0.01);
+    }
+
+    #[test]
+    fn entries_beyond_the_last_page_are_rejected() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+
+        let error = apply(&mut pdf, &[entry(1, "Ghost", 4, 0.0)], &config()).unwrap_err();
+
+        assert_eq!(error.code(), "pdf_processing_failed");
+    }
+
+    #[test]
+    fn script_reads_bookmark_attributes() {
+        let script = outline_script(OutlineSource::Bookmarks);
+
+        assert!(script.contains("querySelectorAll('[data-bookmark]')"));
+        assert!(script.contains("(true && element.dataset.bookmark)"));
+    }
+}
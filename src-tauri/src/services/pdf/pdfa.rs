@@ -0,0 +1,451 @@
+//! PDF/A-2b conversion: output intent, document ID, forbidden features, conformance report
+//!
+//! Chrome embeds (and subsets) every font it draws with, so fonts are checked rather
+//! than embedded here. PDF/A-2 allows transparency once the file has an output intent,
+//! so transparency groups are kept; what has no safe automatic fix is reported as a
+//! blocker instead.
+
+use std::collections::BTreeSet;
+use lopdf::content::Content;
+use lopdf::{dictionary, Dictionary, Document as PdfDocument, Object, ObjectId, Stream, StringFormat};
+use sha2::{Digest, Sha256};
+use crate::models::document::ConformanceReport;
+use crate::models::pdf_options::PdfConformance;
+use crate::services::pdf::icc::{srgb_profile, SRGB_DESCRIPTION};
+
+/// Actions PDF/A-2 forbids (clause 6.5.1), JavaScript first among them
+const FORBIDDEN_ACTIONS: &[&[u8]] = &[
+    b"JavaScript",
+    b"Launch",
+    b"Sound",
+    b"Movie",
+    b"ResetForm",
+    b"ImportData",
+    b"Hide",
+    b"SetOCGState",
+    b"Rendition",
+    b"Trans",
+    b"GoTo3DView",
+];
+
+/// Annotation flags: Invisible, Hidden, NoView, ToggleNoView must be off, Print on
+const HIDING_FLAGS: i64 = 1 | 2 | 32 | 256;
+const PRINT_FLAG: i64 = 4;
+
+/// Fixes what PDF/A forbids and reports what can't be fixed
+///
+/// `original` are the bytes the document was loaded from, hashed into the document ID.
+/// The caller claims conformance in the XMP metadata only when the report has no blockers.
+pub fn prepare(pdf: &mut PdfDocument, conformance: PdfConformance, original: &[u8]) -> ConformanceReport {
+    let mut changes = Vec::new();
+
+    if add_output_intent(pdf) {
+        changes.push(format!("added the {} output intent", SRGB_DESCRIPTION));
+    }
+    if !pdf.trailer.has(b"ID") {
+        let id: Vec<u8> = Sha256::digest(original)[..16].to_vec();
+        let id = Object::String(id, StringFormat::Hexadecimal);
+        pdf.trailer.set("ID", vec![id.clone(), id]);
+        changes.push("added a document ID".to_string());
+    }
+
+    let fixes = strip_forbidden_features(pdf);
+    if fixes.actions > 0 {
+        changes.push(format!("removed {} JavaScript or other forbidden actions", fixes.actions));
+        pdf.prune_objects();
+    }
+    if fixes.annotations > 0 {
+        changes.push(format!("set the print flag on {} annotations", fixes.annotations));
+    }
+    if fixes.graphics_states > 0 {
+        changes.push(format!("removed transfer functions and halftone origins from {} graphics states", fixes.graphics_states));
+    }
+    if fixes.xobjects > 0 {
+        changes.push(format!("removed interpolation, alternates and OPI from {} images and forms", fixes.xobjects));
+    }
+
+    let blockers = blockers(pdf);
+    ConformanceReport { conformance, conformant: blockers.is_empty(), changes, blockers }
+}
+
+/// Adds the sRGB output intent unless a PDF/A one is already there
+fn add_output_intent(pdf: &mut PdfDocument) -> bool {
+    let existing = pdf
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"OutputIntents").ok())
+        .and_then(|intents| pdf.dereference(intents).ok())
+        .and_then(|(_, intents)| intents.as_array().ok())
+        .cloned()
+        .unwrap_or_default();
+    let is_pdfa = |intent: &Object| {
+        pdf.dereference(intent)
+            .and_then(|(_, intent)| intent.as_dict())
+            .and_then(|intent| intent.get(b"S"))
+            .and_then(Object::as_name)
+            .is_ok_and(|subtype| subtype == b"GTS_PDFA1")
+    };
+    if existing.iter().any(is_pdfa) {
+        return false;
+    }
+
+    let profile_id = pdf.add_object(Stream::new(dictionary! { "N" => 3, "Alternate" => "DeviceRGB" }, srgb_profile()));
+    let intent_id = pdf.add_object(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFA1",
+        "OutputConditionIdentifier" => Object::string_literal(SRGB_DESCRIPTION),
+        "Info" => Object::string_literal(SRGB_DESCRIPTION),
+        "RegistryName" => Object::string_literal("http://www.color.org"),
+        "DestOutputProfile" => profile_id,
+    });
+
+    let mut intents = existing;
+    intents.push(intent_id.into());
+    match pdf.catalog_mut() {
+        Ok(catalog) => {
+            catalog.set("OutputIntents", intents);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[derive(Default)]
+struct Fixes {
+    actions: usize,
+    annotations: usize,
+    graphics_states: usize,
+    xobjects: usize,
+}
+
+fn strip_forbidden_features(pdf: &mut PdfDocument) -> Fixes {
+    // Actions stored as objects of their own, referenced from /A or /OpenAction
+    let forbidden_ids: BTreeSet<ObjectId> = pdf
+        .objects
+        .iter()
+        .filter(|(_, object)| object.as_dict().is_ok_and(is_forbidden_action))
+        .map(|(&id, _)| id)
+        .collect();
+    let forbidden = |action: &Object| match action {
+        Object::Dictionary(action) => is_forbidden_action(action),
+        Object::Reference(id) => forbidden_ids.contains(id),
+        _ => false,
+    };
+
+    let mut fixes = Fixes::default();
+    for object in pdf.objects.values_mut() {
+        visit_dictionaries(object, &mut |dictionary| {
+            for key in [b"A".as_slice(), b"OpenAction"] {
+                if dictionary.get(key).is_ok_and(forbidden) {
+                    dictionary.remove(key);
+                    fixes.actions += 1;
+                }
+            }
+            // Additional actions (triggers) and the document-level scripts of the Names tree
+            for key in [b"AA".as_slice(), b"JavaScript"] {
+                if dictionary.remove(key).is_some() {
+                    fixes.actions += 1;
+                }
+            }
+
+            if is_annotation(dictionary) && !is_popup(dictionary) {
+                let flags = dictionary.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+                let fixed = (flags | PRINT_FLAG) & !HIDING_FLAGS;
+                if fixed != flags {
+                    dictionary.set("F", fixed);
+                    fixes.annotations += 1;
+                }
+            }
+
+            // Only graphics state dictionaries use these keys
+            let default_transfer = dictionary.get(b"TR2").and_then(Object::as_name).is_ok_and(|name| name == b"Default");
+            let transfer = dictionary.remove(b"TR").is_some() | (!default_transfer && dictionary.remove(b"TR2").is_some());
+            if transfer | dictionary.remove(b"HTO").is_some() {
+                fixes.graphics_states += 1;
+            }
+
+            let mut xobject_fixed = false;
+            if is_image(dictionary) && dictionary.get(b"Interpolate").and_then(Object::as_bool).unwrap_or(false) {
+                dictionary.set("Interpolate", false);
+                xobject_fixed = true;
+            }
+            if matches!(subtype(dictionary), Some(b"Image" | b"Form")) {
+                xobject_fixed |= dictionary.remove(b"Alternates").is_some() | dictionary.remove(b"OPI").is_some();
+            }
+            if xobject_fixed {
+                fixes.xobjects += 1;
+            }
+        });
+    }
+    fixes
+}
+
+/// What still keeps the document from conforming, deduplicated and sorted
+fn blockers(pdf: &PdfDocument) -> Vec<String> {
+    let mut blockers = BTreeSet::new();
+
+    for object in pdf.objects.values() {
+        if let Ok(stream) = object.as_stream() {
+            if stream.filters().is_ok_and(|filters| filters.contains(&b"LZWDecode".as_slice())) {
+                blockers.insert("LZW-compressed streams are not allowed".to_string());
+            }
+            if stream.dict.has(b"F") {
+                blockers.insert("streams stored in external files are not allowed".to_string());
+            }
+        }
+
+        for dictionary in dictionaries(object) {
+            if let Some(font) = unembedded_font(pdf, dictionary) {
+                blockers.insert(format!("font \"{}\" is not embedded", font));
+            }
+            if dictionary.has(b"EmbeddedFiles") || subtype(dictionary) == Some(b"FileAttachment") {
+                blockers.insert("embedded files are not allowed (they can't be checked for PDF/A)".to_string());
+            }
+            if subtype(dictionary) == Some(b"PS") {
+                blockers.insert("PostScript XObjects are not allowed".to_string());
+            }
+            if dictionary.iter().any(|(_, value)| names_cmyk(value)) {
+                blockers.insert("DeviceCMYK colour needs a CMYK output intent".to_string());
+            }
+            if is_annotation(dictionary) && needs_appearance(dictionary) && !dictionary.has(b"AP") {
+                let subtype = String::from_utf8_lossy(subtype(dictionary).unwrap_or_default());
+                blockers.insert(format!("{} annotation without an appearance stream", subtype));
+            }
+        }
+    }
+
+    if content_streams(pdf).iter().any(|content| draws_cmyk(content)) {
+        blockers.insert("DeviceCMYK colour needs a CMYK output intent".to_string());
+    }
+    blockers.into_iter().collect()
+}
+
+/// BaseFont of a simple or CID font drawn without its font program
+fn unembedded_font(pdf: &PdfDocument, font: &Dictionary) -> Option<String> {
+    if !font.has_type(b"Font") {
+        return None;
+    }
+    // Type0 fonts are checked through their descendant, Type3 glyphs are content streams
+    if matches!(subtype(font), Some(b"Type0" | b"Type3")) {
+        return None;
+    }
+
+    let embedded = font
+        .get(b"FontDescriptor")
+        .and_then(|descriptor| pdf.dereference(descriptor))
+        .and_then(|(_, descriptor)| descriptor.as_dict())
+        .is_ok_and(|descriptor| [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"].iter().any(|key| descriptor.has(key)));
+    if embedded {
+        return None;
+    }
+
+    let name = font.get(b"BaseFont").and_then(Object::as_name).unwrap_or(b"unnamed");
+    Some(String::from_utf8_lossy(name).into_owned())
+}
+
+/// Decoded content of every page and form XObject
+fn content_streams(pdf: &PdfDocument) -> Vec<Vec<u8>> {
+    let pages = pdf.get_pages().into_values().filter_map(|page_id| pdf.get_page_content(page_id).ok());
+    let forms = pdf
+        .objects
+        .values()
+        .filter_map(|object| object.as_stream().ok())
+        .filter(|stream| subtype(&stream.dict) == Some(b"Form"))
+        .filter_map(|stream| stream.get_plain_content().ok());
+    pages.chain(forms).collect()
+}
+
+/// Uses the `k`/`K` operators or names DeviceCMYK as an operand
+fn draws_cmyk(content: &[u8]) -> bool {
+    Content::decode(content).is_ok_and(|content| {
+        content
+            .operations
+            .iter()
+            .any(|operation| matches!(operation.operator.as_str(), "k" | "K") || operation.operands.iter().any(names_cmyk))
+    })
+}
+
+fn names_cmyk(object: &Object) -> bool {
+    match object {
+        Object::Name(name) => name == b"DeviceCMYK" || name == b"CMYK",
+        Object::Array(items) => items.iter().any(names_cmyk),
+        _ => false,
+    }
+}
+
+fn is_forbidden_action(action: &Dictionary) -> bool {
+    action.get(b"S").and_then(Object::as_name).is_ok_and(|kind| FORBIDDEN_ACTIONS.contains(&kind))
+}
+
+/// Annotations are the only dictionaries with both a Subtype and a Rect
+fn is_annotation(dictionary: &Dictionary) -> bool {
+    dictionary.has(b"Subtype") && dictionary.has(b"Rect")
+}
+
+fn is_popup(annotation: &Dictionary) -> bool {
+    subtype(annotation) == Some(b"Popup")
+}
+
+fn is_image(dictionary: &Dictionary) -> bool {
+    subtype(dictionary) == Some(b"Image")
+}
+
+/// Every annotation but links, popups and zero-size ones must carry an appearance
+fn needs_appearance(annotation: &Dictionary) -> bool {
+    if matches!(subtype(annotation), Some(b"Link" | b"Popup")) {
+        return false;
+    }
+    let rect: Vec<f32> = annotation
+        .get(b"Rect")
+        .and_then(Object::as_array)
+        .map(|rect| rect.iter().filter_map(|value| value.as_float().ok()).collect())
+        .unwrap_or_default();
+    rect.len() == 4 && rect[0] != rect[2] && rect[1] != rect[3]
+}
+
+fn subtype(dictionary: &Dictionary) -> Option<&[u8]> {
+    dictionary.get(b"Subtype").and_then(Object::as_name).ok()
+}
+
+/// Calls `visit` on every dictionary inside `object`, nested ones included
+fn visit_dictionaries(object: &mut Object, visit: &mut dyn FnMut(&mut Dictionary)) {
+    let dictionary = match object {
+        Object::Dictionary(dictionary) => dictionary,
+        Object::Stream(stream) => &mut stream.dict,
+        Object::Array(items) => {
+            items.iter_mut().for_each(|item| visit_dictionaries(item, visit));
+            return;
+        }
+        _ => return,
+    };
+    visit(dictionary);
+    for (_, value) in dictionary.iter_mut() {
+        visit_dictionaries(value, visit);
+    }
+}
+
+/// Every dictionary inside `object`, nested ones included
+fn dictionaries(object: &Object) -> Vec<&Dictionary> {
+    let dictionary = match object {
+        Object::Dictionary(dictionary) => dictionary,
+        Object::Stream(stream) => &stream.dict,
+        Object::Array(items) => return items.iter().flat_map(dictionaries).collect(),
+        _ => return Vec::new(),
+    };
+    let mut found = vec![dictionary];
+    found.extend(dictionary.iter().flat_map(|(_, value)| dictionaries(value)));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::pdf::{load, sample_pdf, save};
+
+    fn script(code: &str) -> Dictionary {
+        dictionary! { "S" => "JavaScript", "JS" => Object::string_literal(code) }
+    }
+
+    #[test]
+    fn adds_output_intent_and_document_id() {
+        let data = sample_pdf(2);
+        let mut pdf = load(&data).unwrap();
+
+        let report = prepare(&mut pdf, PdfConformance::PdfA2b, &data);
+
+        assert!(report.conformant);
+        assert!(report.blockers.is_empty());
+        assert_eq!(report.changes, ["added the sRGB IEC61966-2.1 output intent", "added a document ID"]);
+
+        let pdf = load(&save(&mut pdf).unwrap()).unwrap();
+        let intents = pdf.catalog().unwrap().get(b"OutputIntents").unwrap().as_array().unwrap();
+        let intent = pdf.get_dictionary(intents[0].as_reference().unwrap()).unwrap();
+        assert_eq!(intent.get(b"S").unwrap().as_name().unwrap(), b"GTS_PDFA1");
+        let profile_id = intent.get(b"DestOutputProfile").unwrap().as_reference().unwrap();
+        let profile = pdf.get_object(profile_id).unwrap().as_stream().unwrap();
+        assert_eq!(profile.dict.get(b"N").unwrap().as_i64().unwrap(), 3);
+        assert_eq!(profile.content, srgb_profile());
+
+        let id = pdf.trailer.get(b"ID").unwrap().as_array().unwrap();
+        assert_eq!(id.len(), 2);
+        assert_eq!(id[0].as_str().unwrap().len(), 16);
+    }
+
+    #[test]
+    fn preparing_twice_changes_nothing_more() {
+        let data = sample_pdf(1);
+        let mut pdf = load(&data).unwrap();
+        prepare(&mut pdf, PdfConformance::PdfA2b, &data);
+
+        let report = prepare(&mut pdf, PdfConformance::PdfA2b, &data);
+
+        assert!(report.conformant);
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn strips_javascript_and_makes_annotations_printable() {
+        let data = sample_pdf(1);
+        let mut pdf = load(&data).unwrap();
+        let page_id = pdf.get_pages()[&1];
+        let script_id = pdf.add_object(script("app.alert('link')"));
+        let link = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 100.into(), 20.into()],
+            "F" => 2,
+            "A" => script_id,
+        };
+        let page = pdf.get_dictionary_mut(page_id).unwrap();
+        page.set("Annots", vec![Object::Dictionary(link)]);
+        page.set("AA", dictionary! { "O" => script("app.alert('open page')") });
+        let catalog = pdf.catalog_mut().unwrap();
+        catalog.set("OpenAction", script("app.alert('open')"));
+        catalog.set("Names", dictionary! {
+            "JavaScript" => dictionary! { "Names" => vec![Object::string_literal("init"), script_id.into()] },
+        });
+
+        let report = prepare(&mut pdf, PdfConformance::PdfA2b, &data);
+
+        assert!(report.conformant);
+        assert!(report.changes.contains(&"removed 4 JavaScript or other forbidden actions".to_string()));
+        assert!(report.changes.contains(&"set the print flag on 1 annotations".to_string()));
+
+        let saved = save(&mut pdf).unwrap();
+        assert!(!saved.windows(9).any(|window| window == b"app.alert"));
+        let pdf = load(&saved).unwrap();
+        let page = pdf.get_dictionary(pdf.get_pages()[&1]).unwrap();
+        let link = page.get(b"Annots").unwrap().as_array().unwrap()[0].as_dict().unwrap();
+        assert_eq!(link.get(b"F").unwrap().as_i64().unwrap(), PRINT_FLAG);
+        assert!(!link.has(b"A"));
+    }
+
+    #[test]
+    fn unembedded_fonts_and_cmyk_block_conformance() {
+        let data = sample_pdf(1);
+        let mut pdf = load(&data).unwrap();
+        let page_id = pdf.get_pages()[&1];
+        let standard = pdf.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" });
+        let font_file = pdf.add_object(Stream::new(dictionary! {}, b"glyphs".to_vec()));
+        let descriptor = pdf.add_object(dictionary! { "Type" => "FontDescriptor", "FontFile2" => font_file });
+        let embedded = pdf.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "TrueType",
+            "BaseFont" => "AAAAAA+Roboto",
+            "FontDescriptor" => descriptor,
+        });
+        let content_id = pdf.get_page_contents(page_id)[0];
+        pdf.objects.insert(
+            content_id,
+            Stream::new(dictionary! {}, b"0 0 0 1 k BT /F1 12 Tf (Hi) Tj /F2 12 Tf (there) Tj ET".to_vec()).into(),
+        );
+        pdf.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Resources", dictionary! { "Font" => dictionary! { "F1" => standard, "F2" => embedded } });
+
+        let report = prepare(&mut pdf, PdfConformance::PdfA2b, &data);
+
+        assert!(!report.conformant);
+        assert_eq!(report.blockers, ["DeviceCMYK colour needs a CMYK output intent", "font \"Helvetica\" is not embedded"]);
+    }
+}
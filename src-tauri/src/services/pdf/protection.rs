@@ -0,0 +1,111 @@
+//! AES-256 encryption (standard security handler, revision 6) and permission flags
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{Document as PdfDocument, EncryptionState, EncryptionVersion, Permissions};
+use crate::models::pdf_options::PdfProtection;
+use crate::models::print_error::PrintError;
+
+/// Name of the single crypt filter used for both strings and streams
+const CRYPT_FILTER: &[u8] = b"StdCF";
+
+/// Encrypts every string and stream of the document
+///
+/// Must run last: anything added afterwards would be stored in clear text.
+pub fn apply(pdf: &mut PdfDocument, protection: &PdfProtection) -> Result<(), PrintError> {
+    let mut file_encryption_key = [0u8; 32];
+    getrandom::fill(&mut file_encryption_key).map_err(|e| PrintError::pdf("encrypt", e))?;
+
+    // Without an owner password anyone could lift the restrictions, so use one nobody knows
+    let generated_owner_password;
+    let owner_password = match protection.owner_password.as_deref().filter(|password| !password.is_empty()) {
+        Some(password) => password,
+        None => {
+            generated_owner_password = random_password()?;
+            &generated_owner_password
+        }
+    };
+
+    let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    let version = EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(CRYPT_FILTER.to_vec(), crypt_filter)]),
+        file_encryption_key: &file_encryption_key,
+        stream_filter: CRYPT_FILTER.to_vec(),
+        string_filter: CRYPT_FILTER.to_vec(),
+        owner_password,
+        user_password: protection.user_password.as_deref().unwrap_or_default(),
+        permissions: permissions(protection),
+    };
+
+    let state = EncryptionState::try_from(version).map_err(|e| PrintError::pdf("encrypt", e))?;
+    pdf.encrypt(&state).map_err(|e| PrintError::pdf("encrypt", e))
+}
+
+/// Everything allowed except what the flags take away
+fn permissions(protection: &PdfProtection) -> Permissions {
+    let mut permissions = Permissions::all();
+    if protection.no_print {
+        permissions.remove(Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY);
+    }
+    if protection.no_copy {
+        permissions.remove(Permissions::COPYABLE);
+    }
+    if protection.no_edit {
+        permissions.remove(Permissions::MODIFIABLE | Permissions::ANNOTABLE | Permissions::FILLABLE | Permissions::ASSEMBLABLE);
+    }
+    permissions
+}
+
+/// 32 random bytes as hex
+fn random_password() -> Result<String, PrintError> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| PrintError::pdf("encrypt", e))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::pdf::{load, sample_pdf, save};
+
+    fn encrypt(protection: &PdfProtection) -> Vec<u8> {
+        let mut pdf = load(&sample_pdf(2)).unwrap();
+        apply(&mut pdf, protection).unwrap();
+        save(&mut pdf).unwrap()
+    }
+
+    #[test]
+    fn user_password_is_needed_to_read_the_content() {
+        let data = encrypt(&PdfProtection { user_password: Some("payroll".to_string()), ..Default::default() });
+
+        // Contents are no longer readable as written
+        assert!(!data.windows(8).any(|window| window == b"% page 1"));
+        assert!(PdfDocument::load_mem_with_password(&data, "wrong").is_err());
+
+        let pdf = PdfDocument::load_mem_with_password(&data, "payroll").unwrap();
+        let state = pdf.encryption_state.as_ref().unwrap();
+        assert_eq!((state.version(), state.revision()), (5, 6));
+        let page = pdf.get_pages()[&1];
+        assert_eq!(pdf.get_page_content(page).unwrap(), b"% page 1");
+    }
+
+    #[test]
+    fn flags_restrict_documents_that_open_without_password() {
+        let data = encrypt(&PdfProtection { no_print: true, no_copy: true, ..Default::default() });
+
+        let pdf = PdfDocument::load_mem(&data).unwrap();
+        let permissions = pdf.encryption_state.as_ref().unwrap().permissions();
+        assert!(!permissions.intersects(Permissions::PRINTABLE | Permissions::COPYABLE));
+        assert!(permissions.contains(Permissions::COPYABLE_FOR_ACCESSIBILITY | Permissions::MODIFIABLE));
+    }
+
+    #[test]
+    fn no_edit_keeps_printing_and_copying() {
+        let permissions = permissions(&PdfProtection { no_edit: true, ..Default::default() });
+
+        assert!(permissions.contains(Permissions::PRINTABLE | Permissions::COPYABLE));
+        assert!(!permissions.intersects(Permissions::MODIFIABLE | Permissions::FILLABLE | Permissions::ASSEMBLABLE));
+    }
+}
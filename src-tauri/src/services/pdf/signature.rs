@@ -0,0 +1,506 @@
+//! PAdES baseline B signatures: a detached CAdES (CMS) signature over the whole file
+
+use std::fmt;
+use std::fs;
+use cms::builder::{SignedDataBuilder, SignerInfoBuilder};
+use cms::cert::{CertificateChoices, IssuerAndSerialNumber};
+use cms::signed_data::{EncapsulatedContentInfo, SignerIdentifier};
+use der::asn1::{Any, OctetString, PrintableStringRef, SetOfVec, Utf8StringRef};
+use der::oid::db::{rfc4519, rfc5911, rfc5912};
+use der::{Decode, Encode, Tag};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, text_string, Document as PdfDocument, Object, ObjectId, Stream, StringFormat};
+use p12_keystore::KeyStore;
+use rsa::pkcs1v15::{Signature, SigningKey};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::RsaPrivateKey;
+use sha2::{Digest, Sha256};
+use x509_cert::attr::Attribute;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+use x509_cert::Certificate;
+use crate::models::pdf_options::{PdfSignature, SignatureAppearance};
+use crate::models::print_error::PrintError;
+use crate::services::pdf::metadata::{pdf_date, xmp_date};
+use crate::services::pdf::{page_height, save, PT_PER_MM};
+
+/// Bytes reserved in `/Contents` for the CMS signature (room for a few chain certificates)
+const SIGNATURE_CAPACITY: usize = 16 * 1024;
+
+/// Stands in for the byte offsets until the file is laid out; wide enough for any of them
+const BYTE_RANGE_PLACEHOLDER: i64 = 9_999_999_999;
+
+/// Widget annotation flags: Print (4) and Locked (128)
+const WIDGET_FLAGS: i64 = 132;
+
+/// AcroForm SigFlags: SignaturesExist (1) and AppendOnly (2)
+const SIG_FLAGS: i64 = 3;
+
+/// Largest font size used in the visible signature box
+const MAX_FONT_SIZE: f32 = 9.0;
+
+/// Signs the document and returns the final file
+///
+/// The file is written once with placeholders for `/ByteRange` and `/Contents`,
+/// which are then filled in place so every offset stays valid. Nothing may change
+/// the bytes afterwards, so this is the last post-processing step.
+pub fn sign(pdf: &mut PdfDocument, signature: &PdfSignature, now: i64) -> Result<Vec<u8>, PrintError> {
+    let signer = Signer::load(signature)?;
+    add_signature_field(pdf, signature, &signer.name, now)?;
+    let mut data = save(pdf)?;
+
+    let (contents_start, contents_end) = find_contents_placeholder(&data)?;
+    let byte_range = [0, contents_start, contents_end, data.len() - contents_end];
+    write_byte_range(&mut data, &byte_range)?;
+
+    let digest = Sha256::new()
+        .chain_update(&data[..contents_start])
+        .chain_update(&data[contents_end..])
+        .finalize();
+    let cms = signer.cms(&digest)?;
+    if cms.len() > SIGNATURE_CAPACITY {
+        return Err(PrintError::pdf("sign", format!("signature needs {} bytes, {} reserved", cms.len(), SIGNATURE_CAPACITY)));
+    }
+
+    // Inside the `<...>` delimiters; the rest of the placeholder stays zero padding
+    let hex: String = cms.iter().map(|byte| format!("{:02X}", byte)).collect();
+    data[contents_start + 1..contents_start + 1 + hex.len()].copy_from_slice(hex.as_bytes());
+    Ok(data)
+}
+
+/// Private key and certificate chain read from the PKCS#12 file
+struct Signer {
+    key: RsaPrivateKey,
+    /// Signing certificate first
+    chain: Vec<Certificate>,
+    /// Common name of the signing certificate
+    name: String,
+}
+
+impl Signer {
+    fn load(signature: &PdfSignature) -> Result<Self, PrintError> {
+        let path = &signature.pkcs12_path;
+        let invalid = |reason: String| PrintError::InvalidRequest { reason: format!("{}: {}", path.display(), reason) };
+
+        let data = fs::read(path).map_err(|e| PrintError::io(path, e))?;
+        let keystore = KeyStore::from_pkcs12(&data, &signature.password).map_err(|e| invalid(e.to_string()))?;
+        let (_, key_chain) = keystore
+            .private_key_chain()
+            .ok_or_else(|| invalid("no private key in the PKCS#12 file".to_string()))?;
+
+        let key = RsaPrivateKey::from_pkcs8_der(key_chain.key())
+            .map_err(|_| invalid("only RSA keys are supported".to_string()))?;
+        let chain = key_chain
+            .chain()
+            .iter()
+            .map(|certificate| Certificate::from_der(certificate.as_der()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(e.to_string()))?;
+        let Some(certificate) = chain.first() else {
+            return Err(invalid("no certificate for the private key".to_string()));
+        };
+
+        let name = common_name(certificate).unwrap_or_else(|| certificate.tbs_certificate.subject.to_string());
+        Ok(Signer { key, chain, name })
+    }
+
+    /// Detached CMS SignedData over `digest` (SHA-256 of the signed byte ranges)
+    ///
+    /// PAdES baseline B needs the content-type, message-digest and signing-certificate-v2
+    /// signed attributes, and no signing-time: the time goes in the `/M` entry instead.
+    fn cms(&self, digest: &[u8]) -> Result<Vec<u8>, PrintError> {
+        let certificate = &self.chain[0];
+        let content = EncapsulatedContentInfo { econtent_type: rfc5911::ID_DATA, econtent: None };
+        let sid = SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+            issuer: certificate.tbs_certificate.issuer.clone(),
+            serial_number: certificate.tbs_certificate.serial_number.clone(),
+        });
+        let digest_algorithm = AlgorithmIdentifierOwned { oid: rfc5912::ID_SHA_256, parameters: None };
+
+        let signing_key = SigningKey::<Sha256>::new(self.key.clone());
+        let mut signer_info = SignerInfoBuilder::new(&signing_key, sid, digest_algorithm.clone(), &content, Some(digest))
+            .map_err(sign_error)?;
+        signer_info.add_signed_attribute(signing_certificate_v2(certificate)?).map_err(sign_error)?;
+
+        let mut signed_data = SignedDataBuilder::new(&content);
+        signed_data.add_digest_algorithm(digest_algorithm).map_err(sign_error)?;
+        for certificate in &self.chain {
+            signed_data.add_certificate(CertificateChoices::Certificate(certificate.clone())).map_err(sign_error)?;
+        }
+        signed_data.add_signer_info::<_, Signature>(signer_info).map_err(sign_error)?;
+        signed_data.build().map_err(sign_error)?.to_der().map_err(sign_error)
+    }
+}
+
+/// ESS signing-certificate-v2 attribute binding the signature to the certificate (RFC 5035)
+///
+/// `SEQUENCE { SEQUENCE OF ESSCertIDv2 }`, each `ESSCertIDv2` holding only the SHA-256
+/// of the certificate (the default hash algorithm, so it's omitted).
+fn signing_certificate_v2(certificate: &Certificate) -> Result<Attribute, PrintError> {
+    let hash = Sha256::digest(certificate.to_der().map_err(sign_error)?);
+
+    let cert_hash = OctetString::new(hash.to_vec()).and_then(|hash| hash.to_der()).map_err(sign_error)?;
+    let ess_cert_id = Any::new(Tag::Sequence, cert_hash).and_then(|id| id.to_der()).map_err(sign_error)?;
+    let certs = Any::new(Tag::Sequence, ess_cert_id).and_then(|certs| certs.to_der()).map_err(sign_error)?;
+    let value = Any::new(Tag::Sequence, certs).map_err(sign_error)?;
+
+    Ok(Attribute {
+        oid: rfc5911::ID_AA_SIGNING_CERTIFICATE_V_2,
+        values: SetOfVec::try_from(vec![value]).map_err(sign_error)?,
+    })
+}
+
+fn sign_error(error: impl fmt::Display) -> PrintError {
+    PrintError::pdf("sign", error)
+}
+
+fn common_name(certificate: &Certificate) -> Option<String> {
+    certificate
+        .tbs_certificate
+        .subject
+        .0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .find(|attribute| attribute.oid == rfc4519::CN)
+        .and_then(|attribute| {
+            let value = &attribute.value;
+            value
+                .decode_as::<Utf8StringRef<'_>>()
+                .map(|name| name.to_string())
+                .or_else(|_| value.decode_as::<PrintableStringRef<'_>>().map(|name| name.to_string()))
+                .ok()
+        })
+}
+
+/// Adds the signature dictionary with its placeholders, the signature field and its widget
+///
+/// The widget is invisible (empty rectangle on the first page) unless an appearance is given.
+fn add_signature_field(pdf: &mut PdfDocument, signature: &PdfSignature, signer_name: &str, now: i64) -> Result<(), PrintError> {
+    let mut value = dictionary! {
+        "Type" => "Sig",
+        "Filter" => "Adobe.PPKLite",
+        "SubFilter" => "ETSI.CAdES.detached",
+        "ByteRange" => vec![0.into(), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into()],
+        "Contents" => Object::String(vec![0; SIGNATURE_CAPACITY], StringFormat::Hexadecimal),
+        "M" => Object::string_literal(pdf_date(now)),
+        "Name" => text_string(signer_name),
+    };
+    let optional = [
+        ("Reason", &signature.reason),
+        ("Location", &signature.location),
+        ("ContactInfo", &signature.contact_info),
+    ];
+    for (key, text) in optional {
+        if let Some(text) = text.as_deref().filter(|text| !text.is_empty()) {
+            value.set(key, text_string(text));
+        }
+    }
+    let value_id = pdf.add_object(value);
+
+    let page_index = signature.appearance.map_or(0, |appearance| appearance.page_index);
+    let Some(&page_id) = pdf.get_pages().get(&(page_index as u32 + 1)) else {
+        return Err(PrintError::InvalidRequest { reason: format!("signature page {} does not exist", page_index + 1) });
+    };
+
+    let mut widget = dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Sig",
+        "T" => text_string("Signature1"),
+        "V" => value_id,
+        "F" => WIDGET_FLAGS,
+        "P" => page_id,
+        "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+    };
+    if let Some(appearance) = &signature.appearance {
+        let page_height = page_height(pdf, page_id);
+        let (width, height) = (appearance.width_mm * PT_PER_MM, appearance.height_mm * PT_PER_MM);
+        let left = appearance.x_mm * PT_PER_MM;
+        let bottom = page_height - appearance.y_mm * PT_PER_MM - height;
+
+        let stream = appearance_stream(appearance, &appearance_lines(signature, signer_name, now))?;
+        let stream_id = pdf.add_object(stream);
+        widget.set("Rect", vec![left.into(), bottom.into(), (left + width).into(), (bottom + height).into()]);
+        widget.set("AP", dictionary! { "N" => stream_id });
+    }
+    let widget_id = pdf.add_object(widget);
+
+    add_annotation(pdf, page_id, widget_id)?;
+    // Chrome never writes forms, so the signature is the only field
+    let catalog = pdf.catalog_mut().map_err(|e| PrintError::pdf("sign", e))?;
+    catalog.set("AcroForm", dictionary! { "Fields" => vec![widget_id.into()], "SigFlags" => SIG_FLAGS });
+    Ok(())
+}
+
+/// Appends the annotation to the page's `/Annots`, inline or referenced
+fn add_annotation(pdf: &mut PdfDocument, page_id: ObjectId, annotation_id: ObjectId) -> Result<(), PrintError> {
+    let error = |e: lopdf::Error| PrintError::pdf("sign", e);
+    let annots = pdf.get_dictionary(page_id).map_err(error)?.get(b"Annots").ok().cloned();
+
+    match annots {
+        Some(Object::Reference(annots_id)) => {
+            pdf.get_object_mut(annots_id)
+                .and_then(Object::as_array_mut)
+                .map_err(error)?
+                .push(annotation_id.into());
+        }
+        Some(Object::Array(mut annots)) => {
+            annots.push(annotation_id.into());
+            pdf.get_dictionary_mut(page_id).map_err(error)?.set("Annots", annots);
+        }
+        _ => {
+            pdf.get_dictionary_mut(page_id).map_err(error)?.set("Annots", vec![annotation_id.into()]);
+        }
+    }
+    Ok(())
+}
+
+/// Signer, date and the optional reason and location, one per line
+fn appearance_lines(signature: &PdfSignature, signer_name: &str, now: i64) -> Vec<String> {
+    let mut lines = vec![format!("Digitally signed by {}", signer_name), format!("Date: {}", xmp_date(now))];
+    if let Some(reason) = signature.reason.as_deref().filter(|reason| !reason.is_empty()) {
+        lines.push(format!("Reason: {}", reason));
+    }
+    if let Some(location) = signature.location.as_deref().filter(|location| !location.is_empty()) {
+        lines.push(format!("Location: {}", location));
+    }
+    lines
+}
+
+/// Form XObject drawing a thin frame and the lines in Helvetica, clipped to the box
+fn appearance_stream(appearance: &SignatureAppearance, lines: &[String]) -> Result<Stream, PrintError> {
+    let (width, height) = (appearance.width_mm * PT_PER_MM, appearance.height_mm * PT_PER_MM);
+    let padding = 3.0;
+    let font_size = ((height - 2.0 * padding) / (lines.len() as f32 * 1.25)).min(MAX_FONT_SIZE);
+    let leading = font_size * 1.25;
+
+    let mut operations = vec![
+        Operation::new("q", vec![]),
+        Operation::new("re", vec![0.into(), 0.into(), width.into(), height.into()]),
+        Operation::new("W", vec![]),
+        Operation::new("n", vec![]),
+        Operation::new("RG", vec![0.2.into(), 0.2.into(), 0.2.into()]),
+        Operation::new("w", vec![0.5.into()]),
+        Operation::new("re", vec![0.25.into(), 0.25.into(), (width - 0.5).into(), (height - 0.5).into()]),
+        Operation::new("S", vec![]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), font_size.into()]),
+        Operation::new("TL", vec![leading.into()]),
+        Operation::new("Td", vec![padding.into(), (height - padding - font_size).into()]),
+    ];
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            operations.push(Operation::new("T*", vec![]));
+        }
+        operations.push(Operation::new("Tj", vec![Object::String(win_ansi(line), StringFormat::Literal)]));
+    }
+    operations.push(Operation::new("ET", vec![]));
+    operations.push(Operation::new("Q", vec![]));
+
+    let content = Content { operations }.encode().map_err(|e| PrintError::pdf("sign", e))?;
+    let font = dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    };
+    Ok(Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+        },
+        content,
+    ))
+}
+
+/// Latin-1 text as WinAnsi bytes; characters outside it become `?`
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7E | 0xA0..=0xFF => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Offsets of the `<` and just past the `>` of the zero-filled `/Contents` string
+fn find_contents_placeholder(data: &[u8]) -> Result<(usize, usize), PrintError> {
+    let mut placeholder = Vec::with_capacity(SIGNATURE_CAPACITY * 2 + 2);
+    placeholder.push(b'<');
+    placeholder.resize(SIGNATURE_CAPACITY * 2 + 1, b'0');
+    placeholder.push(b'>');
+
+    find(data, &placeholder)
+        .map(|start| (start, start + placeholder.len()))
+        .ok_or_else(|| PrintError::pdf("sign", "signature placeholder not found"))
+}
+
+/// Overwrites the placeholder `/ByteRange` array, padding with spaces to keep its length
+fn write_byte_range(data: &mut [u8], byte_range: &[usize; 4]) -> Result<(), PrintError> {
+    let placeholder = format!("[0 {0} {0} {0}]", BYTE_RANGE_PLACEHOLDER);
+    let start = find(data, placeholder.as_bytes())
+        .ok_or_else(|| PrintError::pdf("sign", "byte range placeholder not found"))?;
+
+    let value = format!("[{} {} {} {}]", byte_range[0], byte_range[1], byte_range[2], byte_range[3]);
+    let padded = format!("{:<width$}", value, width = placeholder.len());
+    data[start..start + placeholder.len()].copy_from_slice(padded.as_bytes());
+    Ok(())
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use cms::content_info::ContentInfo;
+    use cms::signed_data::SignedData;
+    use der::SliceReader;
+    use lopdf::Dictionary;
+    use rsa::pkcs1v15::VerifyingKey;
+    use rsa::pkcs8::DecodePublicKey;
+    use rsa::signature::Verifier;
+    use rsa::RsaPublicKey;
+    use crate::services::pdf::{load, sample_pdf};
+
+    /// Self-signed RSA 2048 certificate "Print Prototype Test Signer", made with:
+    /// `openssl req -x509 -newkey rsa:2048 -nodes -days 36500 -subj "/CN=Print Prototype Test Signer"`
+    /// and `openssl pkcs12 -export -passout pass:test-password`
+    const TEST_PKCS12: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/services/pdf/testdata/signer.p12");
+
+    fn signature() -> PdfSignature {
+        PdfSignature {
+            pkcs12_path: PathBuf::from(TEST_PKCS12),
+            password: "test-password".to_string(),
+            reason: Some("Contract approval".to_string()),
+            location: Some("Zürich".to_string()),
+            contact_info: None,
+            appearance: None,
+        }
+    }
+
+    fn signed(signature: &PdfSignature) -> Vec<u8> {
+        let mut pdf = load(&sample_pdf(2)).unwrap();
+        sign(&mut pdf, signature, 1_709_294_400).unwrap()
+    }
+
+    /// Checks the signature the way a validator does, returning the signed attribute OIDs
+    /// and the signer certificate
+    fn verify(data: &[u8]) -> Result<(Vec<der::oid::ObjectIdentifier>, Certificate), String> {
+        let start = find(data, b"/ByteRange[").ok_or("no byte range")? + b"/ByteRange[".len();
+        let end = start + data[start..].iter().position(|byte| *byte == b']').ok_or("unterminated byte range")?;
+        let range: Vec<usize> = std::str::from_utf8(&data[start..end])
+            .unwrap()
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let [offset, first, second, length] = range[..] else { return Err("byte range needs 4 values".to_string()) };
+        if offset != 0 || second + length != data.len() {
+            return Err("byte range does not cover the whole file".to_string());
+        }
+
+        let hex = std::str::from_utf8(&data[first + 1..second - 1]).unwrap();
+        let der: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+            .collect();
+        // The DER signature is followed by the zero padding of the placeholder
+        let content_info = ContentInfo::decode(&mut SliceReader::new(&der).unwrap()).map_err(|e| e.to_string())?;
+        let signed_data: SignedData = content_info.content.decode_as().map_err(|e| e.to_string())?;
+        let signer_info = signed_data.signer_infos.0.get(0).ok_or("no signer")?;
+        let signed_attributes = signer_info.signed_attrs.as_ref().ok_or("no signed attributes")?;
+
+        let digest = Sha256::new().chain_update(&data[..first]).chain_update(&data[second..]).finalize();
+        let message_digest = signed_attributes
+            .iter()
+            .find(|attribute| attribute.oid == rfc5911::ID_MESSAGE_DIGEST)
+            .ok_or("no message digest")?
+            .values
+            .get(0)
+            .unwrap()
+            .decode_as::<OctetString>()
+            .unwrap();
+        if message_digest.as_bytes() != digest.as_slice() {
+            return Err("document was modified after signing".to_string());
+        }
+
+        let Some(CertificateChoices::Certificate(certificate)) = signed_data.certificates.as_ref().and_then(|set| set.0.get(0)).cloned() else {
+            return Err("no certificate".to_string());
+        };
+        let public_key = RsaPublicKey::from_public_key_der(&certificate.tbs_certificate.subject_public_key_info.to_der().unwrap()).unwrap();
+        let signature = Signature::try_from(signer_info.signature.as_bytes()).unwrap();
+        VerifyingKey::<Sha256>::new(public_key)
+            .verify(&signed_attributes.to_der().unwrap(), &signature)
+            .map_err(|e| e.to_string())?;
+
+        Ok((signed_attributes.iter().map(|attribute| attribute.oid).collect(), certificate))
+    }
+
+    fn signature_value(pdf: &PdfDocument) -> Dictionary {
+        let (_, sig) = pdf.objects.iter().find(|(_, object)| {
+            object.as_dict().is_ok_and(|dict| dict.get(b"Type").and_then(Object::as_name).is_ok_and(|name| name == b"Sig"))
+        }).unwrap();
+        sig.as_dict().unwrap().clone()
+    }
+
+    #[test]
+    fn signature_verifies_against_the_signing_certificate() {
+        let data = signed(&signature());
+
+        let (attributes, certificate) = verify(&data).unwrap();
+
+        assert_eq!(common_name(&certificate).as_deref(), Some("Print Prototype Test Signer"));
+        assert!(attributes.contains(&rfc5911::ID_AA_SIGNING_CERTIFICATE_V_2));
+        assert!(attributes.contains(&rfc5911::ID_CONTENT_TYPE));
+        // PAdES keeps the signing time in /M, never in the CMS
+        assert!(!attributes.contains(&rfc5911::ID_SIGNING_TIME));
+
+        let pdf = load(&data).unwrap();
+        let value = signature_value(&pdf);
+        assert_eq!(value.get(b"SubFilter").unwrap().as_name().unwrap(), b"ETSI.CAdES.detached");
+        assert_eq!(value.get(b"M").unwrap().as_str().unwrap(), b"D:20240301120000+00'00'");
+        let acro_form = pdf.catalog().unwrap().get(b"AcroForm").unwrap().as_dict().unwrap();
+        assert_eq!(acro_form.get(b"SigFlags").unwrap().as_i64().unwrap(), SIG_FLAGS);
+    }
+
+    #[test]
+    fn changing_a_signed_byte_breaks_the_signature() {
+        let mut data = signed(&signature());
+        let page = find(&data, b"/Type/Page/").unwrap();
+        data[page + 6] = b'X';
+
+        assert_eq!(verify(&data).unwrap_err(), "document was modified after signing");
+    }
+
+    #[test]
+    fn visible_box_sits_on_the_chosen_page() {
+        let appearance = SignatureAppearance { page_index: 1, x_mm: 20.0, y_mm: 250.0, width_mm: 70.0, height_mm: 20.0 };
+        let data = signed(&PdfSignature { appearance: Some(appearance), ..signature() });
+
+        assert!(verify(&data).is_ok());
+        let pdf = load(&data).unwrap();
+        let page = pdf.get_dictionary(pdf.get_pages()[&2]).unwrap();
+        let widget_id = page.get(b"Annots").unwrap().as_array().unwrap()[0].as_reference().unwrap();
+        let widget = pdf.get_dictionary(widget_id).unwrap();
+        let rect: Vec<f32> = widget.get(b"Rect").unwrap().as_array().unwrap().iter().map(|value| value.as_float().unwrap()).collect();
+        let expected = [20.0 * PT_PER_MM, 842.0 - 270.0 * PT_PER_MM, 90.0 * PT_PER_MM, 842.0 - 250.0 * PT_PER_MM];
+        assert!(rect.iter().zip(expected).all(|(actual, expected)| (actual - expected).abs() < 0.01), "{:?}", rect);
+
+        let stream_id = widget.get(b"AP").unwrap().as_dict().unwrap().get(b"N").unwrap().as_reference().unwrap();
+        let content = String::from_utf8_lossy(&pdf.get_object(stream_id).unwrap().as_stream().unwrap().content).to_string();
+        assert!(content.contains("(Digitally signed by Print Prototype Test Signer) Tj"));
+        assert!(content.contains("(Location: Z\u{FFFD}rich) Tj"));
+    }
+
+    #[test]
+    fn wrong_password_is_an_invalid_request() {
+        let mut pdf = load(&sample_pdf(1)).unwrap();
+
+        let error = sign(&mut pdf, &PdfSignature { password: "nope".to_string(), ..signature() }, 0).unwrap_err();
+
+        assert_eq!(error.code(), "invalid_request");
+    }
+}
@@ -0,0 +1,678 @@
+use std::process::Command;
+use crate::models::print_config::{PrinterInfo, PrinterState};
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobOptions, JobStatus, PrintJob, ScalingMode};
+use crate::models::print_config::{Orientation, PaperFormat};
+use crate::models::printer_capabilities::{
+    ColorMode, HardwareMargins, MediaSize, PrinterCapabilities, Resolution, Sides,
+};
+use crate::services::ipp::encoding::{group_tag, IppAttribute, IppMessage, IppValue};
+use crate::services::ipp::IppClient;
+use super::PrintBackend;
+
+/// Printer attributes read for [`PrinterCapabilities`]
+const CAPABILITY_ATTRIBUTES: &[&str] = &[
+    "media-supported",
+    "media-default",
+    "orientation-requested-supported",
+    "sides-supported",
+    "print-color-mode-supported",
+    "color-supported",
+    "printer-resolution-supported",
+    "copies-supported",
+    "page-ranges-supported",
+    "multiple-document-handling-supported",
+    "print-scaling-supported",
+    "media-source-supported",
+    "media-top-margin-supported",
+    "media-right-margin-supported",
+    "media-bottom-margin-supported",
+    "media-left-margin-supported",
+];
+
+/// Printer attributes read for [`PrinterInfo`]
+const PRINTER_INFO_ATTRIBUTES: &[&str] = &[
+    "printer-name",
+    "printer-info",
+    "printer-location",
+    "printer-make-and-model",
+    "printer-state",
+    "printer-state-reasons",
+    "printer-is-accepting-jobs",
+];
+
+/// CUPS spooler, driven over IPP
+///
+/// Printer ids are CUPS queue names or full `ipp://` URIs.
+pub struct CupsBackend;
+
+impl PrintBackend for CupsBackend {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError> {
+        // IPP attributes read the same in every locale; lpstat is only the fallback for
+        // schedulers the IPP client can't reach (e.g. a ServerName set in client.conf)
+        match list_printers_ipp() {
+            Err(PrintError::SpoolerUnavailable { .. }) => list_printers_lpstat(),
+            result => result,
+        }
+    }
+
+    fn capabilities(&self, printer: &str) -> Result<PrinterCapabilities, PrintError> {
+        // CUPS answers from the queue's PPD or from the IPP Everywhere printer itself
+        let response = IppClient::for_printer(printer)?.get_printer_attributes(CAPABILITY_ATTRIBUTES)?;
+        Ok(capabilities_from_ipp(&response))
+    }
+
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError> {
+        let attributes = job_attributes(&job.options);
+        IppClient::for_printer(printer)?.print_job(&job.name, "application/pdf", job.data, attributes)
+    }
+
+    fn job_status(&self, printer: &str, job_id: &str) -> Result<JobStatus, PrintError> {
+        IppClient::for_printer(printer)?.get_job_attributes(parse_job_id(job_id)?)
+    }
+
+    fn cancel_job(&self, printer: &str, job_id: &str) -> Result<(), PrintError> {
+        IppClient::for_printer(printer)?.cancel_job(parse_job_id(job_id)?)
+    }
+
+    fn list_jobs(&self, printer: Option<&str>, filter: JobFilter) -> Result<Vec<JobStatus>, PrintError> {
+        let client = match printer {
+            Some(printer) => IppClient::for_printer(printer)?,
+            None => IppClient::cups_server(),
+        };
+
+        // IPP "completed" covers every terminal state, like JobFilter::Finished
+        let which_jobs = match filter {
+            JobFilter::Active => "not-completed",
+            JobFilter::Finished => "completed",
+            JobFilter::All => "all",
+        };
+        client.get_jobs(which_jobs)
+    }
+}
+
+/// Lists the CUPS queues with CUPS-Get-Printers and marks the server's default
+fn list_printers_ipp() -> Result<Vec<PrinterInfo>, PrintError> {
+    let client = IppClient::cups_server();
+    let response = client.cups_get_printers(PRINTER_INFO_ATTRIBUTES)?;
+    let default = client.cups_get_default()?;
+
+    Ok(with_default(printers_from_ipp(&response), default.as_deref()))
+}
+
+/// Builds one [`PrinterInfo`] per printer group of a CUPS-Get-Printers response
+fn printers_from_ipp(response: &IppMessage) -> Vec<PrinterInfo> {
+    response
+        .groups_with_tag(group_tag::PRINTER)
+        .filter_map(|group| {
+            let value = |name: &str| group.attribute(name).and_then(|attribute| attribute.value());
+            let text = |name: &str| {
+                value(name)
+                    .and_then(|value| value.as_str())
+                    .filter(|text| !text.is_empty())
+                    .map(|text| text.to_string())
+            };
+
+            let id = text("printer-name")?;
+            let description = text("printer-info");
+            let state_reasons = group
+                .attribute("printer-state-reasons")
+                .map(|attribute| attribute.values.iter().filter_map(|value| value.as_str()).collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            Some(PrinterInfo {
+                display_name: description.clone().unwrap_or_else(|| id.clone()),
+                is_default: false,
+                state: value("printer-state")
+                    .and_then(|value| value.as_i32())
+                    .and_then(PrinterState::from_ipp)
+                    .unwrap_or(PrinterState::Idle),
+                state_reasons: real_reasons(state_reasons),
+                accepting_jobs: value("printer-is-accepting-jobs").and_then(|value| value.as_bool()).unwrap_or(true),
+                location: text("printer-location"),
+                make_and_model: text("printer-make-and-model"),
+                description,
+                id,
+            })
+        })
+        .collect()
+}
+
+fn list_printers_lpstat() -> Result<Vec<PrinterInfo>, PrintError> {
+    // -l adds description, alerts and location; the C locale keeps the wording parseable
+    let output = Command::new("lpstat")
+        .args(["-l", "-p", "-a", "-d"])
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .env_remove("LANGUAGE")
+        .output()
+        .map_err(|e| PrintError::spooler("lpstat", e))?;
+
+    if !output.status.success() {
+        return Err(PrintError::spooler("lpstat", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(parse_lpstat(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `lpstat -l -p -a -d` output produced in the C locale
+///
+/// Make and model is not part of lpstat output and stays None.
+fn parse_lpstat(output: &str) -> Vec<PrinterInfo> {
+    let mut printers: Vec<PrinterInfo> = Vec::new();
+    let mut rejecting = Vec::new();
+    let mut default = None;
+    // Indented detail lines belong to the printer line above them
+    let mut in_printer = false;
+
+    for line in output.lines() {
+        if let Some(detail) = line.strip_prefix('\t') {
+            let (Some(printer), true) = (printers.last_mut(), in_printer) else { continue };
+            let Some((key, value)) = detail.trim_start().split_once(':') else { continue };
+            let value = value.trim();
+
+            match key {
+                "Description" if !value.is_empty() => {
+                    printer.display_name = value.to_string();
+                    printer.description = Some(value.to_string());
+                }
+                "Location" if !value.is_empty() => printer.location = Some(value.to_string()),
+                "Alerts" => printer.state_reasons = real_reasons(value.split_whitespace().collect()),
+                _ => {}
+            }
+            continue;
+        }
+
+        in_printer = false;
+        if let Some(rest) = line.strip_prefix("printer ") {
+            // "<name> is idle.", "<name> now printing <job>." or "<name> disabled since ..."
+            let Some((id, status)) = rest.split_once(' ') else { continue };
+            let state = if status.starts_with("disabled") {
+                PrinterState::Stopped
+            } else if status.starts_with("now printing") {
+                PrinterState::Printing
+            } else {
+                PrinterState::Idle
+            };
+            printers.push(PrinterInfo { state, ..PrinterInfo::new(id, id) });
+            in_printer = true;
+        } else if let Some(name) = line.strip_prefix("system default destination: ") {
+            default = Some(name.trim().to_string());
+        } else if let Some((name, status)) = line.split_once(' ') {
+            if status.starts_with("not accepting requests") {
+                rejecting.push(name.to_string());
+            }
+        }
+    }
+
+    for printer in &mut printers {
+        printer.accepting_jobs = !rejecting.contains(&printer.id);
+    }
+    with_default(printers, default.as_deref())
+}
+
+/// Drops the "none" placeholder CUPS reports when nothing is wrong
+fn real_reasons(reasons: Vec<&str>) -> Vec<String> {
+    reasons
+        .into_iter()
+        .filter(|reason| *reason != "none")
+        .map(|reason| reason.to_string())
+        .collect()
+}
+
+/// Marks `default` as the default printer, or the first printer if there is no default
+fn with_default(mut printers: Vec<PrinterInfo>, default: Option<&str>) -> Vec<PrinterInfo> {
+    for printer in &mut printers {
+        printer.is_default = Some(printer.id.as_str()) == default;
+    }
+    if !printers.iter().any(|printer| printer.is_default) {
+        if let Some(first) = printers.first_mut() {
+            first.is_default = true;
+        }
+    }
+    printers
+}
+
+/// Maps job options to IPP job template attributes
+///
+/// `print-scaling` is always sent, so the page is fitted as requested whatever the
+/// queue's default is. The other options are only sent when set.
+fn job_attributes(options: &JobOptions) -> Vec<IppAttribute> {
+    let keyword = |name: &str, value: &str| IppAttribute::new(name, IppValue::Keyword(value.to_string()));
+
+    let scaling = match options.scaling {
+        ScalingMode::Fit => "fit",
+        ScalingMode::None => "none",
+        ScalingMode::Fill => "fill",
+    };
+    let mut attributes = vec![keyword("print-scaling", scaling)];
+
+    if options.copies > 1 {
+        attributes.push(IppAttribute::new("copies", IppValue::Integer(options.copies as i32)));
+        attributes.push(keyword(
+            "multiple-document-handling",
+            if options.collate {
+                "separate-documents-collated-copies"
+            } else {
+                "separate-documents-uncollated-copies"
+            },
+        ));
+    }
+
+    if let Some(sides) = options.sides {
+        let sides = match sides {
+            Sides::OneSided => "one-sided",
+            Sides::TwoSidedLongEdge => "two-sided-long-edge",
+            Sides::TwoSidedShortEdge => "two-sided-short-edge",
+        };
+        attributes.push(keyword("sides", sides));
+    }
+
+    if let Some(color_mode) = options.color_mode {
+        let color_mode = match color_mode {
+            ColorMode::Color => "color",
+            ColorMode::Monochrome => "monochrome",
+        };
+        attributes.push(keyword("print-color-mode", color_mode));
+    }
+
+    if !options.page_ranges.is_empty() {
+        attributes.push(IppAttribute {
+            name: "page-ranges".to_string(),
+            values: options
+                .page_ranges
+                .iter()
+                .map(|range| IppValue::Range(range.from as i32, range.to as i32))
+                .collect(),
+        });
+    }
+
+    attributes
+}
+
+/// Maps a Get-Printer-Attributes response to capabilities
+///
+/// Missing attributes fall back to what every printer can do: portrait and
+/// landscape, one-sided, and color only if `color-supported` says so.
+fn capabilities_from_ipp(response: &IppMessage) -> PrinterCapabilities {
+    let values = |name: &str| -> Vec<IppValue> {
+        response
+            .attribute(group_tag::PRINTER, name)
+            .map(|attribute| attribute.values.clone())
+            .unwrap_or_default()
+    };
+    let keywords = |name: &str| -> Vec<String> {
+        values(name).iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
+    };
+
+    let media_names = keywords("media-supported");
+    let custom_sizes = media_names.iter().any(|name| name.starts_with("custom_m"));
+    let media: Vec<MediaSize> = media_names
+        .iter()
+        .filter(|name| !name.starts_with("custom_m"))
+        .filter_map(|name| {
+            let (width_mm, height_mm) = pwg_media_size(name)?;
+            Some(MediaSize {
+                name: name.clone(),
+                width_mm,
+                height_mm,
+                format: PaperFormat::named().iter().find(|f| f.matches_size(width_mm, height_mm)).copied(),
+            })
+        })
+        .collect();
+
+    let paper_formats = PaperFormat::named()
+        .iter()
+        .filter(|format| media.iter().any(|m| format.matches_size(m.width_mm, m.height_mm)))
+        .copied()
+        .collect();
+
+    let mut orientations: Vec<Orientation> = Vec::new();
+    for value in values("orientation-requested-supported") {
+        let orientation = match value.as_i32() {
+            Some(3) | Some(6) => Orientation::Portrait,
+            Some(4) | Some(5) => Orientation::Landscape,
+            _ => continue,
+        };
+        if !orientations.contains(&orientation) {
+            orientations.push(orientation);
+        }
+    }
+    if orientations.is_empty() {
+        orientations = vec![Orientation::Portrait, Orientation::Landscape];
+    }
+
+    let mut sides: Vec<Sides> = keywords("sides-supported").iter().filter_map(|k| Sides::from_ipp(k)).collect();
+    if sides.is_empty() {
+        sides.push(Sides::OneSided);
+    }
+
+    let mut color_modes: Vec<ColorMode> = Vec::new();
+    for mode in keywords("print-color-mode-supported").iter().filter_map(|k| ColorMode::from_ipp(k)) {
+        if !color_modes.contains(&mode) {
+            color_modes.push(mode);
+        }
+    }
+    if color_modes.is_empty() {
+        let color = values("color-supported").first().and_then(|v| v.as_bool()).unwrap_or(false);
+        color_modes = if color { vec![ColorMode::Color, ColorMode::Monochrome] } else { vec![ColorMode::Monochrome] };
+    }
+
+    let resolutions = values("printer-resolution-supported")
+        .iter()
+        .filter_map(|value| match value {
+            // Units: 3 = dots per inch, 4 = dots per cm
+            IppValue::Resolution(x, y, 4) => Some(Resolution {
+                x_dpi: (*x as f32 * 2.54).round() as u32,
+                y_dpi: (*y as f32 * 2.54).round() as u32,
+            }),
+            IppValue::Resolution(x, y, _) => Some(Resolution { x_dpi: *x as u32, y_dpi: *y as u32 }),
+            _ => None,
+        })
+        .collect();
+
+    let max_copies = values("copies-supported").iter().find_map(|value| match value {
+        IppValue::Range(_, max) => Some(*max as u32),
+        _ => None,
+    });
+    // CUPS filters can always pick pages, so assume support unless the printer says otherwise
+    let page_ranges = values("page-ranges-supported").first().and_then(|v| v.as_bool()).unwrap_or(true);
+
+    // Older CUPS versions omit both attributes; their filters collate and scale themselves
+    let handling = keywords("multiple-document-handling-supported");
+    let collate = handling.is_empty() || handling.iter().any(|k| k == "separate-documents-collated-copies");
+    let mut scaling_modes: Vec<ScalingMode> =
+        keywords("print-scaling-supported").iter().filter_map(|k| ScalingMode::from_ipp(k)).collect();
+    if scaling_modes.is_empty() {
+        scaling_modes = vec![ScalingMode::Fit, ScalingMode::None, ScalingMode::Fill];
+    }
+
+    // Margins are listed in hundredths of mm; the smallest is what the hardware can reach
+    let margin = |name: &str| {
+        values(name)
+            .iter()
+            .filter_map(|v| v.as_i32())
+            .min()
+            .map(|hundredths| hundredths as f32 / 100.0)
+            .unwrap_or(0.0)
+    };
+
+    PrinterCapabilities {
+        media,
+        default_media: keywords("media-default").into_iter().next(),
+        paper_formats,
+        custom_sizes,
+        orientations,
+        sides,
+        color_modes,
+        resolutions,
+        max_copies,
+        page_ranges,
+        collate,
+        scaling_modes,
+        input_trays: keywords("media-source-supported"),
+        hardware_margins_mm: HardwareMargins {
+            top: margin("media-top-margin-supported"),
+            right: margin("media-right-margin-supported"),
+            bottom: margin("media-bottom-margin-supported"),
+            left: margin("media-left-margin-supported"),
+        },
+    }
+}
+
+/// Reads the size from a PWG 5101.1 media name such as `iso_a4_210x297mm` or `na_letter_8.5x11in`
+fn pwg_media_size(name: &str) -> Option<(f32, f32)> {
+    let size = name.rsplit('_').next()?;
+    let (size, mm_per_unit) = if let Some(size) = size.strip_suffix("mm") {
+        (size, 1.0)
+    } else {
+        (size.strip_suffix("in")?, 25.4)
+    };
+
+    let (width, height) = size.split_once('x')?;
+    let width: f32 = width.parse().ok()?;
+    let height: f32 = height.parse().ok()?;
+    Some((width * mm_per_unit, height * mm_per_unit))
+}
+
+/// CUPS job ids are positive integers
+fn parse_job_id(job_id: &str) -> Result<i32, PrintError> {
+    job_id
+        .parse()
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| PrintError::JobNotFound { job_id: job_id.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::print_job::PageRange;
+    use crate::services::ipp::encoding::IppGroup;
+
+    /// `LC_ALL=C lpstat -l -p -a -d` on CUPS 2.4 with three queues
+    const LPSTAT_THREE_QUEUES: &str = "\
+printer Front_Desk is idle.  enabled since Tue Oct 14 09:12:03 2025
+\tForm mounted:
+\tContent types: any
+\tPrinter types: unknown
+\tDescription: Front desk laser
+\tAlerts: none
+\tLocation: Ground floor, reception
+\tConnection: direct
+\tInterface: /etc/cups/ppd/Front_Desk.ppd
+\tOn fault: no alert
+\tAfter fault: continue
+\tUsers allowed:
+\t\t(all)
+printer Label_Printer disabled since Mon Oct 13 17:40:11 2025 -
+\tPaused
+\tForm mounted:
+\tDescription: 
+\tAlerts: paused media-empty-error
+\tLocation: 
+printer Lobby_Inkjet now printing Lobby_Inkjet-57.  enabled since Tue Oct 14 10:01:45 2025
+\tDescription: Lobby color inkjet
+\tAlerts: none
+\tLocation: Lobby
+Front_Desk accepting requests since Tue Oct 14 09:12:03 2025
+Label_Printer not accepting requests since Mon Oct 13 17:40:11 2025 -
+\tRejecting Jobs
+Lobby_Inkjet accepting requests since Tue Oct 14 10:01:45 2025
+system default destination: Lobby_Inkjet
+";
+
+    /// `LC_ALL=C lpstat -l -p -a -d` with a single queue and no default set
+    const LPSTAT_NO_DEFAULT: &str = "\
+printer PDF is idle.  enabled since Wed Oct 15 08:00:00 2025
+\tDescription: Virtual PDF Printer
+\tAlerts: none
+\tLocation: 
+PDF accepting requests since Wed Oct 15 08:00:00 2025
+no system default destination
+";
+
+    fn attribute(name: &str, values: Vec<IppValue>) -> IppAttribute {
+        IppAttribute { name: name.to_string(), values }
+    }
+
+    fn keywords(values: &[&str]) -> Vec<IppValue> {
+        values.iter().map(|v| IppValue::Keyword(v.to_string())).collect()
+    }
+
+    #[test]
+    fn reads_pwg_media_sizes() {
+        assert_eq!(pwg_media_size("iso_a4_210x297mm"), Some((210.0, 297.0)));
+        let (w, h) = pwg_media_size("na_letter_8.5x11in").unwrap();
+        assert!((w - 215.9).abs() < 1e-3 && (h - 279.4).abs() < 1e-3);
+        assert_eq!(pwg_media_size("roll_max_36x100in").map(|(w, _)| w.round()), Some(914.0));
+        assert_eq!(pwg_media_size("photo"), None);
+    }
+
+    #[test]
+    fn maps_printer_attributes_to_capabilities() {
+        let mut response = IppMessage::request(0, 1);
+        for attr in [
+            attribute("media-supported", keywords(&[
+                "iso_a4_210x297mm", "na_letter_8.5x11in", "na_ledger_11x17in", "oe_photo-l_3.5x5in",
+                "custom_min_3x5in", "custom_max_8.5x14in",
+            ])),
+            attribute("media-default", keywords(&["iso_a4_210x297mm"])),
+            attribute("orientation-requested-supported", vec![IppValue::Enum(3)]),
+            attribute("sides-supported", keywords(&["one-sided", "two-sided-long-edge", "two-sided-short-edge"])),
+            attribute("print-color-mode-supported", keywords(&["auto", "monochrome", "color"])),
+            attribute("printer-resolution-supported", vec![
+                IppValue::Resolution(300, 300, 3),
+                IppValue::Resolution(236, 236, 4),
+            ]),
+            attribute("media-source-supported", keywords(&["auto", "main", "manual"])),
+            attribute("copies-supported", vec![IppValue::Range(1, 99)]),
+            attribute("page-ranges-supported", vec![IppValue::Boolean(false)]),
+            attribute("multiple-document-handling-supported", keywords(&["separate-documents-uncollated-copies"])),
+            attribute("print-scaling-supported", keywords(&["auto", "fit", "fill"])),
+            attribute("media-top-margin-supported", vec![IppValue::Integer(423), IppValue::Integer(0)]),
+            attribute("media-left-margin-supported", vec![IppValue::Integer(318)]),
+        ] {
+            response.add(group_tag::PRINTER, attr);
+        }
+
+        let caps = capabilities_from_ipp(&response);
+
+        assert_eq!(caps.media.len(), 4);
+        assert_eq!(caps.media[0].format, Some(PaperFormat::A4));
+        assert_eq!(caps.media[3].format, None);
+        assert_eq!(caps.paper_formats, vec![PaperFormat::A4, PaperFormat::Letter, PaperFormat::Tabloid, PaperFormat::Ledger]);
+        assert!(caps.custom_sizes);
+        assert_eq!(caps.default_media.as_deref(), Some("iso_a4_210x297mm"));
+        assert_eq!(caps.orientations, vec![Orientation::Portrait]);
+        assert_eq!(caps.sides.len(), 3);
+        assert_eq!(caps.color_modes, vec![ColorMode::Monochrome, ColorMode::Color]);
+        assert_eq!(caps.resolutions, vec![Resolution { x_dpi: 300, y_dpi: 300 }, Resolution { x_dpi: 599, y_dpi: 599 }]);
+        assert_eq!(caps.max_copies, Some(99));
+        assert!(!caps.page_ranges);
+        assert!(!caps.collate);
+        assert_eq!(caps.scaling_modes, vec![ScalingMode::Fit, ScalingMode::Fill]);
+        assert_eq!(caps.input_trays, vec!["auto", "main", "manual"]);
+        assert_eq!(caps.hardware_margins_mm, HardwareMargins { top: 0.0, right: 0.0, bottom: 0.0, left: 3.18 });
+    }
+
+    #[test]
+    fn missing_attributes_fall_back_to_basic_printing() {
+        let mut response = IppMessage::request(0, 1);
+        response.add(group_tag::PRINTER, attribute("color-supported", vec![IppValue::Boolean(false)]));
+
+        let caps = capabilities_from_ipp(&response);
+
+        assert!(caps.paper_formats.is_empty());
+        assert_eq!(caps.orientations, vec![Orientation::Portrait, Orientation::Landscape]);
+        assert_eq!(caps.sides, vec![Sides::OneSided]);
+        assert_eq!(caps.color_modes, vec![ColorMode::Monochrome]);
+        assert!(caps.page_ranges);
+        assert!(caps.collate);
+        assert_eq!(caps.scaling_modes.len(), 3);
+    }
+
+    #[test]
+    fn default_options_only_send_scaling() {
+        let attributes = job_attributes(&JobOptions::default());
+
+        assert_eq!(attributes, vec![IppAttribute::new("print-scaling", IppValue::Keyword("fit".into()))]);
+    }
+
+    #[test]
+    fn maps_job_options_to_ipp_attributes() {
+        let options = JobOptions {
+            copies: 3,
+            collate: false,
+            sides: Some(Sides::TwoSidedShortEdge),
+            color_mode: Some(ColorMode::Monochrome),
+            page_ranges: vec![PageRange { from: 1, to: 2 }, PageRange { from: 5, to: 5 }],
+            scaling: ScalingMode::None,
+        };
+
+        let attributes = job_attributes(&options);
+        let value = |name: &str| attributes.iter().find(|a| a.name == name).unwrap().values.clone();
+
+        assert_eq!(value("print-scaling"), keywords(&["none"]));
+        assert_eq!(value("copies"), vec![IppValue::Integer(3)]);
+        assert_eq!(value("multiple-document-handling"), keywords(&["separate-documents-uncollated-copies"]));
+        assert_eq!(value("sides"), keywords(&["two-sided-short-edge"]));
+        assert_eq!(value("print-color-mode"), keywords(&["monochrome"]));
+        assert_eq!(value("page-ranges"), vec![IppValue::Range(1, 2), IppValue::Range(5, 5)]);
+    }
+
+    #[test]
+    fn parses_lpstat_states_details_and_default() {
+        let printers = parse_lpstat(LPSTAT_THREE_QUEUES);
+
+        assert_eq!(printers.len(), 3);
+        let front = &printers[0];
+        assert_eq!(front.id, "Front_Desk");
+        assert_eq!(front.display_name, "Front desk laser");
+        assert_eq!(front.description.as_deref(), Some("Front desk laser"));
+        assert_eq!(front.location.as_deref(), Some("Ground floor, reception"));
+        assert_eq!(front.state, PrinterState::Idle);
+        assert!(front.state_reasons.is_empty());
+        assert!(front.accepting_jobs && !front.is_default);
+
+        let label = &printers[1];
+        assert_eq!(label.display_name, "Label_Printer");
+        assert_eq!(label.description, None);
+        assert_eq!(label.location, None);
+        assert_eq!(label.state, PrinterState::Stopped);
+        assert_eq!(label.state_reasons, vec!["paused", "media-empty-error"]);
+        assert!(!label.accepting_jobs);
+
+        let lobby = &printers[2];
+        assert_eq!(lobby.state, PrinterState::Printing);
+        assert_eq!(lobby.location.as_deref(), Some("Lobby"));
+        assert!(lobby.is_default);
+        assert!(printers.iter().all(|p| p.make_and_model.is_none()));
+    }
+
+    #[test]
+    fn lpstat_without_default_marks_the_first_printer() {
+        let printers = parse_lpstat(LPSTAT_NO_DEFAULT);
+
+        assert_eq!(printers.len(), 1);
+        assert_eq!(printers[0].display_name, "Virtual PDF Printer");
+        assert!(printers[0].is_default);
+        assert!(parse_lpstat("").is_empty());
+    }
+
+    #[test]
+    fn maps_cups_get_printers_response() {
+        let mut response = IppMessage::request(0x0000, 1);
+        response.groups.push(IppGroup {
+            tag: group_tag::PRINTER,
+            attributes: vec![
+                attribute("printer-name", vec![IppValue::Name("Office".into())]),
+                attribute("printer-info", vec![IppValue::Text("Office printer".into())]),
+                attribute("printer-location", vec![IppValue::Text("Room 2.14".into())]),
+                attribute("printer-make-and-model", vec![IppValue::Text("HP LaserJet M404".into())]),
+                attribute("printer-state", vec![IppValue::Enum(5)]),
+                attribute("printer-state-reasons", keywords(&["paused", "toner-low-warning"])),
+                attribute("printer-is-accepting-jobs", vec![IppValue::Boolean(false)]),
+            ],
+        });
+        response.groups.push(IppGroup {
+            tag: group_tag::PRINTER,
+            attributes: vec![
+                attribute("printer-name", vec![IppValue::Name("Plotter".into())]),
+                attribute("printer-info", vec![IppValue::Text(String::new())]),
+                attribute("printer-state-reasons", keywords(&["none"])),
+            ],
+        });
+
+        let printers = with_default(printers_from_ipp(&response), Some("Plotter"));
+
+        assert_eq!(printers[0].display_name, "Office printer");
+        assert_eq!(printers[0].location.as_deref(), Some("Room 2.14"));
+        assert_eq!(printers[0].make_and_model.as_deref(), Some("HP LaserJet M404"));
+        assert_eq!(printers[0].state, PrinterState::Stopped);
+        assert_eq!(printers[0].state_reasons, vec!["paused", "toner-low-warning"]);
+        assert!(!printers[0].accepting_jobs && !printers[0].is_default);
+
+        assert_eq!(printers[1], PrinterInfo { is_default: true, ..PrinterInfo::new("Plotter", "Plotter") });
+    }
+}
@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use crate::models::print_config::PrinterInfo;
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobState, JobStatus, PrintJob, ScalingMode};
+use crate::models::printer_capabilities::{ColorMode, PrinterCapabilities, Sides};
+use super::{unix_time, PrintBackend};
+
+/// Id of the single pseudo-printer exposed by the directory backend
+pub const DIRECTORY_PRINTER_ID: &str = "directory";
+
+/// "Print to directory": every job is written as a PDF file
+///
+/// Useful for kiosks, CI and debugging. Jobs complete as soon as the file is written.
+pub struct DirectoryBackend {
+    dir: PathBuf,
+    /// Jobs written during this session, oldest first
+    jobs: Mutex<Vec<JobStatus>>,
+}
+
+impl DirectoryBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        DirectoryBackend {
+            dir,
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn check_printer(&self, printer: &str) -> Result<(), PrintError> {
+        if printer == DIRECTORY_PRINTER_ID {
+            Ok(())
+        } else {
+            Err(PrintError::PrinterNotFound { printer: printer.to_string() })
+        }
+    }
+
+    fn find(&self, job_id: &str) -> Result<JobStatus, PrintError> {
+        let jobs = self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        jobs.iter()
+            .find(|job| job.job_id == job_id)
+            .cloned()
+            .ok_or_else(|| PrintError::JobNotFound { job_id: job_id.to_string() })
+    }
+}
+
+impl PrintBackend for DirectoryBackend {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError> {
+        Ok(vec![PrinterInfo {
+            is_default: true,
+            ..PrinterInfo::new(DIRECTORY_PRINTER_ID, format!("Save to {}", self.dir.display()))
+        }])
+    }
+
+    fn capabilities(&self, printer: &str) -> Result<PrinterCapabilities, PrintError> {
+        self.check_printer(printer)?;
+
+        // The PDF is written as rendered: one copy, every page, no duplex, color or scaling change
+        Ok(PrinterCapabilities {
+            sides: vec![Sides::OneSided],
+            color_modes: vec![ColorMode::Color],
+            max_copies: Some(1),
+            page_ranges: false,
+            scaling_modes: vec![ScalingMode::Fit],
+            ..PrinterCapabilities::unrestricted()
+        })
+    }
+
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError> {
+        self.check_printer(printer)?;
+        fs::create_dir_all(&self.dir).map_err(|e| PrintError::io(&self.dir, e))?;
+
+        let mut jobs = self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let job_id = (jobs.len() + 1).to_string();
+
+        let path = self.dir.join(format!("{}-{}.pdf", uuid::Uuid::new_v4(), file_stem(&job.name)));
+        fs::write(&path, &job.data).map_err(|e| PrintError::io(&path, e))?;
+
+        let now = unix_time();
+        let status = JobStatus {
+            job_id,
+            printer: printer.to_string(),
+            name: Some(job.name),
+            state: JobState::Completed,
+            state_reasons: vec!["job-completed-successfully".to_string()],
+            created_at: Some(now),
+            completed_at: Some(now),
+        };
+        jobs.push(status.clone());
+        Ok(status)
+    }
+
+    fn job_status(&self, printer: &str, job_id: &str) -> Result<JobStatus, PrintError> {
+        self.check_printer(printer)?;
+        self.find(job_id)
+    }
+
+    fn cancel_job(&self, printer: &str, job_id: &str) -> Result<(), PrintError> {
+        self.check_printer(printer)?;
+        self.find(job_id)?;
+
+        // Files are written synchronously, so there is never anything left to cancel
+        Err(PrintError::JobRejected {
+            printer: printer.to_string(),
+            reason: format!("job {} has already completed", job_id),
+        })
+    }
+
+    fn list_jobs(&self, printer: Option<&str>, filter: JobFilter) -> Result<Vec<JobStatus>, PrintError> {
+        if let Some(printer) = printer {
+            self.check_printer(printer)?;
+        }
+
+        let jobs = self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(jobs.iter().filter(|job| filter.matches(job.state)).cloned().collect())
+    }
+}
+
+/// Job name reduced to characters that are safe in file names
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim_end_matches(".pdf")
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .take(64)
+        .collect();
+
+    if stem.is_empty() { "document".to_string() } else { stem }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_each_job_as_a_pdf_file() {
+        let dir = std::env::temp_dir().join(format!("print-dir-{}", uuid::Uuid::new_v4()));
+        let backend = DirectoryBackend::new(dir.clone());
+
+        let job = PrintJob {
+            name: "Q3 report/final".to_string(),
+            data: b"%PDF-1.7".to_vec(),
+            page_count: 1,
+            options: Default::default(),
+        };
+        let status = backend.submit_job(DIRECTORY_PRINTER_ID, job).unwrap();
+
+        assert_eq!(status.job_id, "1");
+        assert_eq!(status.state, JobState::Completed);
+        let files: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].to_string_lossy().ends_with("-Q3_report_final.pdf"));
+        assert_eq!(fs::read(&files[0]).unwrap(), b"%PDF-1.7");
+
+        assert_eq!(backend.job_status(DIRECTORY_PRINTER_ID, "1").unwrap().state, JobState::Completed);
+        assert_eq!(backend.job_status(DIRECTORY_PRINTER_ID, "2").unwrap_err().code(), "job_not_found");
+        assert_eq!(backend.job_status("other", "1").unwrap_err().code(), "printer_not_found");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
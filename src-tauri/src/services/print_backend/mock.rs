@@ -0,0 +1,136 @@
+use std::sync::Mutex;
+use crate::models::print_config::PrinterInfo;
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobState, JobStatus, PrintJob};
+use crate::models::printer_capabilities::PrinterCapabilities;
+use super::{unix_time, PrintBackend};
+
+/// In-memory backend that records jobs instead of printing them
+///
+/// Jobs stay pending until cancelled, so the job commands can be exercised
+/// without a printer.
+pub struct MockBackend {
+    printers: Vec<PrinterInfo>,
+    capabilities: PrinterCapabilities,
+    jobs: Mutex<Vec<(JobStatus, PrintJob)>>,
+}
+
+impl MockBackend {
+    pub fn new(printers: Vec<PrinterInfo>) -> Self {
+        MockBackend {
+            printers,
+            capabilities: PrinterCapabilities::unrestricted(),
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Reports `capabilities` for every printer
+    #[cfg(test)]
+    pub fn with_capabilities(mut self, capabilities: PrinterCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Jobs submitted so far, oldest first
+    #[cfg(test)]
+    pub fn submitted(&self) -> Vec<(JobStatus, PrintJob)> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(JobStatus, PrintJob)>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check_printer(&self, printer: &str) -> Result<(), PrintError> {
+        if self.printers.iter().any(|p| p.id == printer) {
+            Ok(())
+        } else {
+            Err(PrintError::PrinterNotFound { printer: printer.to_string() })
+        }
+    }
+}
+
+impl Default for MockBackend {
+    /// One default printer named "Mock_Printer"
+    fn default() -> Self {
+        MockBackend::new(vec![PrinterInfo {
+            is_default: true,
+            ..PrinterInfo::new("Mock_Printer", "Mock Printer")
+        }])
+    }
+}
+
+impl PrintBackend for MockBackend {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError> {
+        Ok(self.printers.clone())
+    }
+
+    fn capabilities(&self, printer: &str) -> Result<PrinterCapabilities, PrintError> {
+        self.check_printer(printer)?;
+        Ok(self.capabilities.clone())
+    }
+
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError> {
+        self.check_printer(printer)?;
+
+        let mut jobs = self.lock();
+        let status = JobStatus {
+            job_id: (jobs.len() + 1).to_string(),
+            printer: printer.to_string(),
+            name: Some(job.name.clone()),
+            state: JobState::Pending,
+            state_reasons: vec!["none".to_string()],
+            created_at: Some(unix_time()),
+            completed_at: None,
+        };
+        jobs.push((status.clone(), job));
+        Ok(status)
+    }
+
+    fn job_status(&self, printer: &str, job_id: &str) -> Result<JobStatus, PrintError> {
+        self.check_printer(printer)?;
+
+        self.lock()
+            .iter()
+            .find(|(status, _)| status.job_id == job_id)
+            .map(|(status, _)| status.clone())
+            .ok_or_else(|| PrintError::JobNotFound { job_id: job_id.to_string() })
+    }
+
+    fn cancel_job(&self, printer: &str, job_id: &str) -> Result<(), PrintError> {
+        self.check_printer(printer)?;
+
+        let mut jobs = self.lock();
+        let (status, _) = jobs
+            .iter_mut()
+            .find(|(status, _)| status.job_id == job_id)
+            .ok_or_else(|| PrintError::JobNotFound { job_id: job_id.to_string() })?;
+
+        if status.state.is_terminal() {
+            return Err(PrintError::JobRejected {
+                printer: printer.to_string(),
+                reason: format!("job {} is already {:?}", job_id, status.state).to_lowercase(),
+            });
+        }
+
+        status.state = JobState::Canceled;
+        status.state_reasons = vec!["job-canceled-by-user".to_string()];
+        status.completed_at = Some(unix_time());
+        Ok(())
+    }
+
+    fn list_jobs(&self, printer: Option<&str>, filter: JobFilter) -> Result<Vec<JobStatus>, PrintError> {
+        if let Some(printer) = printer {
+            self.check_printer(printer)?;
+        }
+
+        Ok(self
+            .lock()
+            .iter()
+            .map(|(status, _)| status)
+            .filter(|status| printer.is_none_or(|printer| status.printer == printer))
+            .filter(|status| filter.matches(status.state))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,76 @@
+//! Destinations for rendered documents (OS spooler, network printers, directory, mock)
+
+pub mod cups;
+pub mod directory;
+pub mod mock;
+pub mod network;
+#[cfg(target_os = "windows")]
+pub mod windows;
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::models::app_config::{AppConfig, BackendConfig};
+use crate::models::print_config::PrinterInfo;
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobStatus, PrintJob};
+use crate::models::printer_capabilities::PrinterCapabilities;
+
+/// Where print jobs go
+///
+/// Commands only talk to this trait, so the print flow can run against the mock in
+/// tests or write PDFs to a directory instead of the OS spooler.
+pub trait PrintBackend: Send + Sync {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError>;
+
+    /// Media, duplex, color, resolutions, trays and margins the printer supports
+    fn capabilities(&self, printer: &str) -> Result<PrinterCapabilities, PrintError>;
+
+    /// Queues the job on `printer` and returns its initial status
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError>;
+
+    fn job_status(&self, printer: &str, job_id: &str) -> Result<JobStatus, PrintError>;
+
+    fn cancel_job(&self, printer: &str, job_id: &str) -> Result<(), PrintError>;
+
+    /// Jobs of `printer`, or of every printer when None, oldest first
+    fn list_jobs(&self, printer: Option<&str>, filter: JobFilter) -> Result<Vec<JobStatus>, PrintError>;
+}
+
+/// Backend shared by all commands through Tauri state
+pub type SharedBackend = Arc<dyn PrintBackend>;
+
+/// Builds the backend selected in the app configuration, plus any network printers
+pub fn from_config(config: &AppConfig) -> Result<SharedBackend, PrintError> {
+    let backend: SharedBackend = match &config.print_backend {
+        BackendConfig::System => system_backend(),
+        BackendConfig::Cups => Arc::new(cups::CupsBackend),
+        BackendConfig::Directory { path } => Arc::new(directory::DirectoryBackend::new(path.clone())),
+        BackendConfig::Mock => Arc::new(mock::MockBackend::default()),
+    };
+
+    if config.network_printers.is_empty() {
+        return Ok(backend);
+    }
+    Ok(Arc::new(network::NetworkBackend::new(backend, &config.network_printers)?))
+}
+
+/// Current Unix time in seconds, for backends that timestamp jobs themselves
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// The OS spooler: PowerShell on Windows, CUPS everywhere else
+fn system_backend() -> SharedBackend {
+    #[cfg(target_os = "windows")]
+    {
+        Arc::new(windows::WindowsBackend)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Arc::new(cups::CupsBackend)
+    }
+}
@@ -0,0 +1,300 @@
+use std::time::Duration;
+use crate::models::app_config::NetworkPrinterConfig;
+use crate::models::print_config::PrinterInfo;
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobState, JobStatus, PrintJob, ScalingMode};
+use crate::models::printer_capabilities::{PrinterCapabilities, Sides};
+use crate::services::lpd::{LpdQueue, LPD_PORT};
+use crate::services::raw_socket::{RawSocketPrinter, RAW_PORT};
+use crate::services::tcp::split_host_port;
+use super::{unix_time, PrintBackend, SharedBackend};
+
+/// How a network printer is reached
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkTarget {
+    Raw(RawSocketPrinter),
+    Lpd(LpdQueue),
+}
+
+impl NetworkTarget {
+    /// Parses `socket://host[:port]` or `lpd://host[:port]/queue`
+    pub fn parse(uri: &str, timeout: Duration) -> Result<Self, PrintError> {
+        let invalid = |reason: &str| PrintError::InvalidRequest {
+            reason: format!("network printer {}: {}", uri, reason),
+        };
+
+        let (scheme, rest) = uri.split_once("://").ok_or_else(|| invalid("expected socket:// or lpd://"))?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "socket" => {
+                let (host, port) = split_host_port(authority, RAW_PORT).ok_or_else(|| invalid("bad host"))?;
+                Ok(NetworkTarget::Raw(RawSocketPrinter { host, port, timeout }))
+            }
+            "lpd" => {
+                let (host, port) = split_host_port(authority, LPD_PORT).ok_or_else(|| invalid("bad host"))?;
+                let queue = path.trim_end_matches('/');
+                if queue.is_empty() || queue.contains(|c: char| c.is_whitespace() || c.is_control()) {
+                    return Err(invalid("expected a queue name after the host"));
+                }
+                Ok(NetworkTarget::Lpd(LpdQueue { host, port, queue: queue.to_string(), timeout }))
+            }
+            _ => Err(invalid("expected socket:// or lpd://")),
+        }
+    }
+}
+
+/// Printers without a spooler queue, listed next to those of the configured backend
+///
+/// Jobs for these are sent straight to the device; every other printer id is
+/// handed to `inner`. Neither protocol reports job progress, so network jobs can't
+/// be queried or cancelled once sent.
+pub struct NetworkBackend {
+    inner: SharedBackend,
+    printers: Vec<(PrinterInfo, NetworkTarget)>,
+}
+
+impl NetworkBackend {
+    pub fn new(inner: SharedBackend, configs: &[NetworkPrinterConfig]) -> Result<Self, PrintError> {
+        let mut printers = Vec::new();
+        for config in configs {
+            let target = NetworkTarget::parse(&config.uri, Duration::from_secs(config.timeout_secs.max(1)))?;
+            let display_name = config.name.clone().unwrap_or_else(|| config.id.clone());
+            let info = PrinterInfo {
+                description: Some(config.uri.clone()),
+                ..PrinterInfo::new(config.id.clone(), display_name)
+            };
+            printers.push((info, target));
+        }
+
+        Ok(NetworkBackend { inner, printers })
+    }
+
+    fn target(&self, printer: &str) -> Option<&NetworkTarget> {
+        self.printers
+            .iter()
+            .find(|(info, _)| info.id == printer)
+            .map(|(_, target)| target)
+    }
+}
+
+impl PrintBackend for NetworkBackend {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError> {
+        // Network printers stay usable when the spooler is down
+        let mut printers = match self.inner.list_printers() {
+            Ok(printers) => printers,
+            Err(_) if !self.printers.is_empty() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        printers.extend(self.printers.iter().map(|(info, _)| info.clone()));
+
+        // Mark first as default if none was marked
+        if !printers.iter().any(|printer| printer.is_default) {
+            if let Some(first) = printers.first_mut() {
+                first.is_default = true;
+            }
+        }
+
+        Ok(printers)
+    }
+
+    fn capabilities(&self, printer: &str) -> Result<PrinterCapabilities, PrintError> {
+        match self.target(printer) {
+            // The document is sent as-is: copies are repeated (so always collated),
+            // nothing else can be set (one-sided being what a raw document prints as)
+            Some(_) => Ok(PrinterCapabilities {
+                sides: vec![Sides::OneSided],
+                color_modes: Vec::new(),
+                page_ranges: false,
+                scaling_modes: vec![ScalingMode::Fit],
+                ..PrinterCapabilities::unrestricted()
+            }),
+            None => self.inner.capabilities(printer),
+        }
+    }
+
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError> {
+        let Some(target) = self.target(printer) else {
+            return self.inner.submit_job(printer, job);
+        };
+
+        let created_at = unix_time();
+        let (job_id, state) = match target {
+            // The printer closing the socket is all the confirmation there is
+            NetworkTarget::Raw(socket) => {
+                let copies = job.options.copies.max(1);
+                for sent in 0..copies {
+                    socket.send(printer, &job.data).map_err(|error| match error {
+                        // Earlier copies are already printing, so say how far it got
+                        PrintError::PrinterUnavailable { printer, reason } if sent > 0 => PrintError::PrinterUnavailable {
+                            printer,
+                            reason: format!("{} (after {} of {} copies were sent)", reason, sent, copies),
+                        },
+                        error => error,
+                    })?;
+                }
+                (uuid::Uuid::new_v4().to_string(), JobState::Completed)
+            }
+            // Accepted into the remote queue, printed later
+            NetworkTarget::Lpd(queue) => {
+                let job_number = queue.print(printer, &job.name, &job.data, job.options.copies)?;
+                (job_number.to_string(), JobState::Pending)
+            }
+        };
+
+        Ok(JobStatus {
+            job_id,
+            printer: printer.to_string(),
+            name: Some(job.name),
+            state,
+            state_reasons: Vec::new(),
+            created_at: Some(created_at),
+            completed_at: state.is_terminal().then(unix_time),
+        })
+    }
+
+    fn job_status(&self, printer: &str, job_id: &str) -> Result<JobStatus, PrintError> {
+        match self.target(printer) {
+            Some(_) => Err(untracked(printer)),
+            None => self.inner.job_status(printer, job_id),
+        }
+    }
+
+    fn cancel_job(&self, printer: &str, job_id: &str) -> Result<(), PrintError> {
+        match self.target(printer) {
+            Some(_) => Err(untracked(printer)),
+            None => self.inner.cancel_job(printer, job_id),
+        }
+    }
+
+    fn list_jobs(&self, printer: Option<&str>, filter: JobFilter) -> Result<Vec<JobStatus>, PrintError> {
+        match printer {
+            Some(printer) if self.target(printer).is_some() => Err(untracked(printer)),
+            _ => self.inner.list_jobs(printer, filter),
+        }
+    }
+}
+
+fn untracked(printer: &str) -> PrintError {
+    PrintError::InvalidRequest {
+        reason: format!("jobs sent to network printer {} cannot be tracked", printer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+    use crate::models::print_job::JobOptions;
+    use crate::services::print_backend::mock::MockBackend;
+
+    fn network_printer(id: &str, uri: &str) -> NetworkPrinterConfig {
+        NetworkPrinterConfig { id: id.to_string(), name: None, uri: uri.to_string(), timeout_secs: 5 }
+    }
+
+    #[test]
+    fn parses_socket_and_lpd_uris() {
+        let timeout = Duration::from_secs(5);
+
+        assert_eq!(
+            NetworkTarget::parse("socket://10.0.0.7", timeout).unwrap(),
+            NetworkTarget::Raw(RawSocketPrinter { host: "10.0.0.7".to_string(), port: 9100, timeout })
+        );
+        assert_eq!(
+            NetworkTarget::parse("lpd://printserver:5515/labels", timeout).unwrap(),
+            NetworkTarget::Lpd(LpdQueue {
+                host: "printserver".to_string(),
+                port: 5515,
+                queue: "labels".to_string(),
+                timeout,
+            })
+        );
+        assert!(NetworkTarget::parse("lpd://printserver", timeout).is_err());
+        assert!(NetworkTarget::parse("ipp://printserver/ipp/print", timeout).is_err());
+    }
+
+    #[test]
+    fn network_printers_are_listed_after_the_spooler_ones() {
+        let backend = NetworkBackend::new(
+            Arc::new(MockBackend::default()),
+            &[network_printer("dock", "socket://10.0.0.7")],
+        )
+        .unwrap();
+
+        let printers = backend.list_printers().unwrap();
+
+        assert_eq!(printers.len(), 2);
+        assert!(printers[0].is_default);
+        assert_eq!(printers[1].id, "dock");
+        assert_eq!(printers[1].display_name, "dock");
+        assert_eq!(backend.capabilities("dock").unwrap().sides, vec![Sides::OneSided]);
+        assert!(backend.job_status("dock", "1").is_err());
+        assert_eq!(backend.list_jobs(None, JobFilter::All).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn raw_jobs_go_to_the_socket_once_per_copy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            (0..2)
+                .map(|_| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut received = Vec::new();
+                    stream.read_to_end(&mut received).unwrap();
+                    received
+                })
+                .collect::<Vec<_>>()
+        });
+        let backend = NetworkBackend::new(
+            Arc::new(MockBackend::default()),
+            &[network_printer("dock", &format!("socket://127.0.0.1:{}", port))],
+        )
+        .unwrap();
+
+        let status = backend
+            .submit_job("dock", PrintJob {
+                name: "Label".to_string(),
+                data: b"%PDF-1.7".to_vec(),
+                page_count: 1,
+                options: JobOptions { copies: 2, ..JobOptions::default() },
+            })
+            .unwrap();
+
+        assert_eq!(status.state, JobState::Completed);
+        assert_eq!(server.join().unwrap(), vec![b"%PDF-1.7".to_vec(), b"%PDF-1.7".to_vec()]);
+    }
+
+    #[test]
+    fn failed_copies_report_how_many_were_sent() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.read_to_end(&mut Vec::new()).unwrap();
+            // Stop listening before confirming the first copy, so the second is refused
+            drop(listener);
+        });
+        let backend = NetworkBackend::new(
+            Arc::new(MockBackend::default()),
+            &[network_printer("dock", &format!("socket://127.0.0.1:{}", port))],
+        )
+        .unwrap();
+
+        let error = backend
+            .submit_job("dock", PrintJob {
+                name: "Label".to_string(),
+                data: b"%PDF-1.7".to_vec(),
+                page_count: 1,
+                options: JobOptions { copies: 3, ..JobOptions::default() },
+            })
+            .unwrap_err();
+        server.join().unwrap();
+
+        assert_eq!(error.code(), "printer_unavailable");
+        assert!(error.to_string().contains("after 1 of 3 copies were sent"), "{}", error);
+    }
+}
@@ -0,0 +1,211 @@
+use std::fs;
+use std::process::Command;
+use serde::Deserialize;
+use crate::models::print_config::{PrinterInfo, PrinterState};
+use crate::models::print_error::PrintError;
+use crate::models::print_job::{JobFilter, JobState, JobStatus, PrintJob, ScalingMode};
+use crate::models::printer_capabilities::{PrinterCapabilities, Sides};
+use super::PrintBackend;
+
+/// Windows spooler driven through PowerShell
+///
+/// `Start-Process -Verb Print` hands the file to the default PDF viewer, so no job
+/// id comes back and jobs cannot be tracked or cancelled.
+pub struct WindowsBackend;
+
+impl PrintBackend for WindowsBackend {
+    fn list_printers(&self) -> Result<Vec<PrinterInfo>, PrintError> {
+        // Win32_Printer as JSON: property names and numeric states don't depend on the UI language
+        let output = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "Get-CimInstance -ClassName Win32_Printer | \
+                 Select-Object Name, Default, Location, Comment, DriverName, WorkOffline, PrinterStatus | \
+                 ConvertTo-Json",
+            ])
+            .output()
+            .map_err(|e| PrintError::spooler("PowerShell", e))?;
+
+        if !output.status.success() {
+            return Err(PrintError::spooler("PowerShell", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        parse_printers(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn capabilities(&self, _printer: &str) -> Result<PrinterCapabilities, PrintError> {
+        // The print verb passes no job options, so only the printer defaults apply;
+        // one-sided is assumed to be the default, so asking for it explicitly still works
+        Ok(PrinterCapabilities {
+            sides: vec![Sides::OneSided],
+            color_modes: Vec::new(),
+            max_copies: Some(1),
+            page_ranges: false,
+            scaling_modes: vec![ScalingMode::Fit],
+            ..PrinterCapabilities::unrestricted()
+        })
+    }
+
+    fn submit_job(&self, printer: &str, job: PrintJob) -> Result<JobStatus, PrintError> {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let pdf_path = std::env::temp_dir().join(format!("print-{}.pdf", job_id));
+        fs::write(&pdf_path, &job.data).map_err(|e| PrintError::io(&pdf_path, e))?;
+
+        let ps_command = format!(
+            "Start-Process -FilePath '{}' -Verb Print -ArgumentList '/d:\"{}\"'",
+            pdf_path.to_string_lossy(), printer
+        );
+
+        let output = Command::new("powershell")
+            .args(["-Command", &ps_command])
+            .output()
+            .map_err(|e| PrintError::spooler("PowerShell", e));
+
+        let _ = fs::remove_file(&pdf_path);
+        let output = output?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(classify_spooler_failure(printer, &stderr));
+        }
+
+        Ok(JobStatus {
+            job_id,
+            printer: printer.to_string(),
+            name: Some(job.name),
+            state: JobState::Pending,
+            state_reasons: Vec::new(),
+            created_at: None,
+            completed_at: None,
+        })
+    }
+
+    fn job_status(&self, _printer: &str, _job_id: &str) -> Result<JobStatus, PrintError> {
+        Err(untracked())
+    }
+
+    fn cancel_job(&self, _printer: &str, _job_id: &str) -> Result<(), PrintError> {
+        Err(untracked())
+    }
+
+    fn list_jobs(&self, _printer: Option<&str>, _filter: JobFilter) -> Result<Vec<JobStatus>, PrintError> {
+        Err(untracked())
+    }
+}
+
+/// Win32_Printer properties selected by `list_printers`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Win32Printer {
+    name: String,
+    #[serde(default)]
+    default: bool,
+    location: Option<String>,
+    comment: Option<String>,
+    driver_name: Option<String>,
+    #[serde(default)]
+    work_offline: bool,
+    /// 3 idle, 4 printing, 5 warming up, 7 offline
+    printer_status: Option<u16>,
+}
+
+/// Parses `ConvertTo-Json` output, which is a bare object for a single printer
+fn parse_printers(json: &str) -> Result<Vec<PrinterInfo>, PrintError> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| PrintError::spooler("PowerShell", e))?;
+    let entries = match value {
+        serde_json::Value::Array(entries) => entries,
+        entry => vec![entry],
+    };
+
+    let mut printers = Vec::new();
+    for entry in entries {
+        let printer: Win32Printer = serde_json::from_value(entry).map_err(|e| PrintError::spooler("PowerShell", e))?;
+        let non_empty = |text: Option<String>| text.filter(|text| !text.trim().is_empty());
+
+        let offline = printer.work_offline || printer.printer_status == Some(7);
+        printers.push(PrinterInfo {
+            id: printer.name.clone(),
+            display_name: printer.name,
+            is_default: printer.default,
+            state: match printer.printer_status {
+                _ if offline => PrinterState::Stopped,
+                Some(4) | Some(5) => PrinterState::Printing,
+                _ => PrinterState::Idle,
+            },
+            state_reasons: if offline { vec!["offline-report".to_string()] } else { Vec::new() },
+            accepting_jobs: true,
+            location: non_empty(printer.location),
+            make_and_model: non_empty(printer.driver_name),
+            description: non_empty(printer.comment),
+        });
+    }
+
+    // Mark first as default if none was marked
+    if !printers.iter().any(|printer| printer.is_default) {
+        if let Some(first) = printers.first_mut() {
+            first.is_default = true;
+        }
+    }
+
+    Ok(printers)
+}
+
+fn untracked() -> PrintError {
+    PrintError::InvalidRequest {
+        reason: "jobs sent through the Windows print verb cannot be tracked".to_string(),
+    }
+}
+
+/// Maps spooler stderr output to the error taxonomy
+fn classify_spooler_failure(printer: &str, stderr: &str) -> PrintError {
+    let reason = stderr.trim().to_string();
+    let lower = reason.to_lowercase();
+
+    if lower.contains("does not exist") || lower.contains("unknown destination") || lower.contains("not found") {
+        PrintError::PrinterNotFound { printer: printer.to_string() }
+    } else if lower.contains("not accepting") || lower.contains("disabled") || lower.contains("offline") || lower.contains("paused") {
+        PrintError::PrinterUnavailable { printer: printer.to_string(), reason }
+    } else {
+        PrintError::JobRejected { printer: printer.to_string(), reason }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_win32_printer_json() {
+        let json = r#"[
+            { "Name": "Microsoft Print to PDF", "Default": false, "Location": "", "Comment": "",
+              "DriverName": "Microsoft Print To PDF", "WorkOffline": false, "PrinterStatus": 3 },
+            { "Name": "Office", "Default": true, "Location": "Room 2.14", "Comment": "Shared laser",
+              "DriverName": "HP Universal Printing PCL 6", "WorkOffline": true, "PrinterStatus": 3 }
+        ]"#;
+
+        let printers = parse_printers(json).unwrap();
+
+        assert_eq!(printers[0].state, PrinterState::Idle);
+        assert_eq!(printers[0].location, None);
+        assert!(!printers[0].is_default);
+        assert_eq!(printers[1].description.as_deref(), Some("Shared laser"));
+        assert_eq!(printers[1].make_and_model.as_deref(), Some("HP Universal Printing PCL 6"));
+        assert_eq!(printers[1].state, PrinterState::Stopped);
+        assert!(printers[1].is_default);
+    }
+
+    #[test]
+    fn single_printer_is_a_bare_object() {
+        let printers = parse_printers(r#"{ "Name": "Label", "PrinterStatus": 4 }"#).unwrap();
+
+        assert_eq!(printers.len(), 1);
+        assert_eq!(printers[0].state, PrinterState::Printing);
+        assert!(printers[0].is_default);
+        assert!(parse_printers("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::print_error::PrintError;
+use crate::models::print_job::JobState;
+use crate::models::print_progress::{PrintProgress, PrintStage};
+use crate::services::print_backend::SharedBackend;
+
+/// How often a submitted job is polled for state changes
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Jobs still unfinished after this long stop being watched
+const JOB_WATCH_LIMIT: Duration = Duration::from_secs(30 * 60);
+
+/// Receives the progress events of a print call
+pub type ProgressSink = Arc<dyn Fn(PrintProgress) + Send + Sync>;
+
+/// One running print or save call: reports its progress and notices cancellation
+pub struct PrintTask {
+    id: String,
+    canceled: Arc<AtomicBool>,
+    sink: Option<ProgressSink>,
+}
+
+impl PrintTask {
+    /// Task that reports nowhere and cannot be canceled
+    #[cfg(test)]
+    pub fn silent() -> Self {
+        PrintTask {
+            id: uuid::Uuid::new_v4().to_string(),
+            canceled: Arc::new(AtomicBool::new(false)),
+            sink: None,
+        }
+    }
+
+    /// Reports the next stage, or fails if the task was canceled in the meantime
+    ///
+    /// Called between the slow steps, so a cancel takes effect at the next one.
+    pub fn advance(&self, stage: PrintStage) -> Result<(), PrintError> {
+        if self.canceled.load(Ordering::SeqCst) {
+            return Err(PrintError::PrintCanceled { task_id: self.id.clone() });
+        }
+        self.report(stage);
+        Ok(())
+    }
+
+    /// Reports a stage regardless of cancellation (e.g. job states after submission)
+    pub fn report(&self, stage: PrintStage) {
+        if let Some(sink) = &self.sink {
+            sink(PrintProgress { task_id: self.id.clone(), stage });
+        }
+    }
+
+    /// Polls the submitted job in the background and reports each state change
+    ///
+    /// Stops once the job is finished, its state can't be read, or after
+    /// [`JOB_WATCH_LIMIT`]. Nothing is polled when no one listens.
+    pub fn watch_job(&self, backend: SharedBackend, printer: String, job_id: String, state: JobState) {
+        let Some(sink) = self.sink.clone() else { return };
+        if state.is_terminal() {
+            return;
+        }
+
+        let task_id = self.id.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            let mut state = state;
+
+            while started.elapsed() < JOB_WATCH_LIMIT {
+                thread::sleep(JOB_POLL_INTERVAL);
+
+                let Ok(status) = backend.job_status(&printer, &job_id) else { return };
+                if status.state != state {
+                    state = status.state;
+                    sink(PrintProgress {
+                        task_id: task_id.clone(),
+                        stage: PrintStage::JobState { job_id: status.job_id, state },
+                    });
+                }
+                if state.is_terminal() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// Print calls currently running, by task id (stored in Tauri state)
+#[derive(Default)]
+pub struct PrintTasks {
+    running: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+impl PrintTasks {
+    /// Registers a task under `id` (a new one if None) until the returned guard drops
+    ///
+    /// Fails if a task with the same id is still running.
+    pub fn start(&self, id: Option<String>, sink: ProgressSink) -> Result<RunningTask, PrintError> {
+        let task = PrintTask {
+            id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            canceled: Arc::new(AtomicBool::new(false)),
+            sink: Some(sink),
+        };
+
+        let mut running = lock(&self.running);
+        if running.contains_key(&task.id) {
+            return Err(PrintError::InvalidRequest {
+                reason: format!("print {} is already running", task.id),
+            });
+        }
+        running.insert(task.id.clone(), task.canceled.clone());
+        drop(running);
+
+        Ok(RunningTask { running: self.running.clone(), task })
+    }
+
+    /// Flags a running task as canceled
+    pub fn cancel(&self, id: &str) -> Result<(), PrintError> {
+        let running = lock(&self.running);
+        let canceled = running.get(id).ok_or_else(|| PrintError::InvalidRequest {
+            reason: format!("no print {} is running", id),
+        })?;
+
+        canceled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn lock(running: &Mutex<HashMap<String, Arc<AtomicBool>>>) -> MutexGuard<'_, HashMap<String, Arc<AtomicBool>>> {
+    running.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Task registered in [`PrintTasks`], unregistered on drop
+///
+/// Owns its registration, so it can move onto a worker thread while still cancelable.
+pub struct RunningTask {
+    running: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    task: PrintTask,
+}
+
+impl Deref for RunningTask {
+    type Target = PrintTask;
+
+    fn deref(&self) -> &PrintTask {
+        &self.task
+    }
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        lock(&self.running).remove(&self.task.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_sink() -> (ProgressSink, Arc<Mutex<Vec<PrintProgress>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        (Arc::new(move |progress| recorded.lock().unwrap().push(progress)), events)
+    }
+
+    #[test]
+    fn stages_are_reported_with_the_task_id() {
+        let tasks = PrintTasks::default();
+        let (sink, events) = recording_sink();
+
+        let task = tasks.start(Some("print-1".to_string()), sink).unwrap();
+        task.advance(PrintStage::LoadingHtml).unwrap();
+        task.advance(PrintStage::Rendering { page: 1, pages: 3 }).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].task_id, "print-1");
+        assert_eq!(events[1].stage, PrintStage::Rendering { page: 1, pages: 3 });
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(json, serde_json::json!({ "task_id": "print-1", "stage": "rendering", "page": 1, "pages": 3 }));
+    }
+
+    #[test]
+    fn canceled_tasks_stop_at_the_next_stage() {
+        let tasks = PrintTasks::default();
+        let (sink, events) = recording_sink();
+        let task = tasks.start(Some("print-2".to_string()), sink).unwrap();
+
+        tasks.cancel("print-2").unwrap();
+        let error = task.advance(PrintStage::WritingPdf).unwrap_err();
+
+        assert_eq!(error.code(), "print_canceled");
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn finished_tasks_can_no_longer_be_canceled() {
+        let tasks = PrintTasks::default();
+        let (sink, _) = recording_sink();
+        drop(tasks.start(Some("print-3".to_string()), sink).unwrap());
+
+        assert_eq!(tasks.cancel("print-3").unwrap_err().code(), "invalid_request");
+    }
+
+    #[test]
+    fn running_task_ids_cannot_be_reused() {
+        let tasks = PrintTasks::default();
+        let (sink, _) = recording_sink();
+        let first = tasks.start(Some("print-4".to_string()), sink.clone()).unwrap();
+
+        let duplicate = tasks.start(Some("print-4".to_string()), sink.clone());
+        assert_eq!(duplicate.err().unwrap().code(), "invalid_request");
+
+        // The refused call must not unregister the running task
+        tasks.cancel("print-4").unwrap();
+        assert_eq!(first.advance(PrintStage::WritingPdf).unwrap_err().code(), "print_canceled");
+        drop(first);
+        assert!(tasks.start(Some("print-4".to_string()), sink).is_ok());
+    }
+}
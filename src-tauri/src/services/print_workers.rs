@@ -0,0 +1,218 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use crate::models::app_config::QueueConfig;
+use crate::models::print_error::PrintError;
+
+type Work = Box<dyn FnOnce() + Send>;
+
+/// Threads that run rendering and spooling off Tauri's async runtime (stored in Tauri state)
+///
+/// At most `max_concurrent_jobs` jobs run at once; further jobs wait in a FIFO queue
+/// of up to `max_queued_jobs`, beyond which they are refused with `queue_full`.
+pub struct PrintWorkers {
+    sender: Mutex<Sender<Work>>,
+    /// Jobs running or waiting
+    pending: Arc<AtomicUsize>,
+    max_concurrent_jobs: usize,
+    max_queued_jobs: usize,
+}
+
+impl PrintWorkers {
+    pub fn new(config: &QueueConfig) -> Self {
+        let max_concurrent_jobs = config.max_concurrent_jobs.max(1);
+        let (sender, receiver) = mpsc::channel::<Work>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for index in 0..max_concurrent_jobs {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("print-worker-{}", index))
+                .spawn(move || work_loop(&receiver))
+                .expect("failed to spawn print worker thread");
+        }
+
+        PrintWorkers {
+            sender: Mutex::new(sender),
+            pending: Arc::new(AtomicUsize::new(0)),
+            max_concurrent_jobs,
+            max_queued_jobs: config.max_queued_jobs,
+        }
+    }
+
+    /// True if a job submitted now would have to wait for a free worker
+    pub fn is_busy(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) >= self.max_concurrent_jobs
+    }
+
+    /// Runs `job` on a worker thread and resolves with its result
+    pub fn run<T, F>(&self, job: F) -> JobResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, PrintError> + Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot { result: None, waker: None }));
+
+        let limit = self.max_concurrent_jobs + self.max_queued_jobs;
+        let admitted = self
+            .pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| (pending < limit).then_some(pending + 1));
+        if admitted.is_err() {
+            fill(&slot, Err(PrintError::QueueFull { limit }));
+            return JobResult { slot };
+        }
+
+        let pending = self.pending.clone();
+        let job_slot = slot.clone();
+        let work: Work = Box::new(move || {
+            // A panicking job must still answer its caller and leave the worker alive
+            let result = panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|payload| {
+                Err(PrintError::Internal { reason: format!("print job panicked: {}", panic_message(&*payload)) })
+            });
+            pending.fetch_sub(1, Ordering::SeqCst);
+            fill(&job_slot, result);
+        });
+
+        let sender = self.sender.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if sender.send(work).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            fill(&slot, Err(PrintError::Internal { reason: "no print worker threads left".to_string() }));
+        }
+
+        JobResult { slot }
+    }
+}
+
+/// Text of a `panic!` payload, which is a `&str` or `String` unless thrown with `panic_any`
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload")
+}
+
+fn work_loop(receiver: &Mutex<Receiver<Work>>) {
+    loop {
+        // Only hold the lock while taking the next job, not while running it
+        let next = receiver.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).recv();
+        match next {
+            Ok(work) => work(),
+            Err(_) => return,
+        }
+    }
+}
+
+struct Slot<T> {
+    result: Option<Result<T, PrintError>>,
+    waker: Option<Waker>,
+}
+
+fn fill<T>(slot: &Mutex<Slot<T>>, result: Result<T, PrintError>) {
+    let mut slot = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    slot.result = Some(result);
+    if let Some(waker) = slot.waker.take() {
+        waker.wake();
+    }
+}
+
+/// Result of a job handed to [`PrintWorkers::run`]
+pub struct JobResult<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for JobResult<T> {
+    type Output = Result<T, PrintError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::sync_channel;
+    use std::task::Wake;
+    use std::time::Duration;
+
+    /// Minimal executor: polls the future, parking the thread until it is woken
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(thread::Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park_timeout(Duration::from_millis(50)),
+            }
+        }
+    }
+
+    fn workers(max_concurrent_jobs: usize, max_queued_jobs: usize) -> PrintWorkers {
+        PrintWorkers::new(&QueueConfig { max_concurrent_jobs, max_queued_jobs })
+    }
+
+    #[test]
+    fn jobs_run_on_worker_threads() {
+        let workers = workers(2, 4);
+
+        let name = block_on(workers.run(|| Ok(thread::current().name().map(str::to_string))));
+
+        assert!(name.unwrap().unwrap().starts_with("print-worker-"));
+    }
+
+    #[test]
+    fn excess_jobs_wait_then_overflow_the_queue() {
+        let workers = workers(1, 1);
+        let (release, blocked) = sync_channel::<()>(0);
+        let blocked = Mutex::new(blocked);
+
+        let running = workers.run(move || {
+            blocked.lock().unwrap().recv().unwrap();
+            Ok(1)
+        });
+        let queued = workers.run(|| Ok(2));
+        assert!(workers.is_busy());
+
+        let refused = block_on(workers.run(|| Ok(3)));
+        assert_eq!(refused.unwrap_err().code(), "queue_full");
+
+        release.send(()).unwrap();
+        assert_eq!(block_on(running).unwrap(), 1);
+        assert_eq!(block_on(queued).unwrap(), 2);
+        assert!(!workers.is_busy());
+    }
+
+    #[test]
+    fn panicking_jobs_fail_without_killing_the_worker() {
+        let workers = workers(1, 0);
+
+        let failed = block_on(workers.run(|| -> Result<(), PrintError> { panic!("boom") }));
+        let error = failed.unwrap_err();
+        assert_eq!(error.code(), "internal_error");
+        assert_eq!(error.to_string(), "Internal error: print job panicked: boom");
+
+        assert_eq!(block_on(workers.run(|| Ok("still alive"))).unwrap(), "still alive");
+    }
+}
@@ -0,0 +1,97 @@
+//! Raw TCP printing (HP JetDirect / AppSocket), where the printer reads the job
+//! from a plain socket, usually on port 9100
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::Shutdown;
+use std::time::Duration;
+use crate::models::print_error::PrintError;
+use crate::services::tcp;
+
+/// Default JetDirect port
+pub const RAW_PORT: u16 = 9100;
+
+/// How long to wait for the printer to close the connection after the last byte
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Printer reached over a raw socket
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawSocketPrinter {
+    pub host: String,
+    pub port: u16,
+    pub timeout: Duration,
+}
+
+impl RawSocketPrinter {
+    /// Streams `data` to the printer as-is
+    ///
+    /// The protocol has no acknowledgement: once every byte is written the write side
+    /// is closed and the printer is given [`CLOSE_TIMEOUT`] to close its side. Anything
+    /// it sends back (status bytes) is discarded; a printer that keeps the connection
+    /// open still counts as having received the job.
+    pub fn send(&self, printer: &str, data: &[u8]) -> Result<(), PrintError> {
+        let unavailable = |reason: String| PrintError::PrinterUnavailable {
+            printer: printer.to_string(),
+            reason,
+        };
+
+        let mut stream = tcp::connect(&self.host, self.port, self.timeout).map_err(unavailable)?;
+
+        stream
+            .write_all(data)
+            .and_then(|_| stream.flush())
+            .map_err(|e| unavailable(format!("sending job: {}", e)))?;
+        stream
+            .shutdown(Shutdown::Write)
+            .map_err(|e| unavailable(format!("sending job: {}", e)))?;
+
+        stream.set_read_timeout(Some(CLOSE_TIMEOUT)).ok();
+        let mut buffer = [0; 512];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(_) => continue,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(()),
+                // Some printers reset instead of closing once they have the data
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => return Ok(()),
+                Err(e) => return Err(unavailable(format!("waiting for printer: {}", e))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn printer(port: u16) -> RawSocketPrinter {
+        RawSocketPrinter { host: "127.0.0.1".to_string(), port, timeout: Duration::from_secs(5) }
+    }
+
+    #[test]
+    fn streams_the_job_until_the_printer_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            stream.read_to_end(&mut received).unwrap();
+            stream.write_all(b"\x12status").unwrap();
+            received
+        });
+
+        printer(port).send("Dock", b"^XA^FDlabel^FS^XZ").unwrap();
+
+        assert_eq!(server.join().unwrap(), b"^XA^FDlabel^FS^XZ");
+    }
+
+    #[test]
+    fn unreachable_printer_is_unavailable() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+        let error = printer(port).send("Dock", b"x").unwrap_err();
+
+        assert_eq!(error.code(), "printer_unavailable");
+    }
+}
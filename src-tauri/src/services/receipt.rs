@@ -0,0 +1,240 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::Duration;
+use headless_chrome::protocol::cdp::Page;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::Value;
+use crate::models::print_error::PrintError;
+use crate::models::print_progress::PrintStage;
+use crate::models::receipt::{ReceiptDestination, ReceiptRequest, ReceiptWidth};
+use crate::services::document_renderer::{mm_to_css_px, DocumentRenderer};
+use crate::services::escpos::{self, GrayImage};
+use crate::services::raw_socket::RawSocketPrinter;
+use crate::services::render_readiness::wait_until_ready;
+
+/// Time allowed to connect to a network receipt printer and hand over the job
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Script returning the height of the receipt in CSS pixels
+const RECEIPT_HEIGHT_SCRIPT: &str = "document.querySelector('.receipt').getBoundingClientRect().height";
+
+/// Renders the receipt HTML and encodes it as an ESC/POS job
+///
+/// The content is laid out in a column as wide as the printable area, captured at
+/// exactly one pixel per printer dot and dithered to black and white.
+pub fn render_receipt(renderer: &DocumentRenderer<'_>, request: &ReceiptRequest) -> Result<Vec<u8>, PrintError> {
+    let tab = renderer.open(&build_receipt_html(&request.html_content, request.width))?;
+    wait_until_ready(&tab, request.render_timeout_ms, false)?;
+
+    let result = tab.evaluate(RECEIPT_HEIGHT_SCRIPT, false)
+        .map_err(|e| PrintError::browser("receipt", e))?;
+    let height = match result.value.as_ref().and_then(Value::as_f64) {
+        Some(height) if height > 0.0 => height,
+        _ => return Err(PrintError::InvalidRequest { reason: "receipt has no content".to_string() }),
+    };
+
+    renderer.advance(PrintStage::Rendering { page: 1, pages: 1 })?;
+    let width = mm_to_css_px(request.width.printable_mm()) as f64;
+    let png = tab
+        .call_method(Page::CaptureScreenshot {
+            format: Some(Page::CaptureScreenshotFormatOption::Png),
+            quality: None,
+            clip: Some(Page::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width,
+                height,
+                scale: request.width.dots() as f64 / width,
+            }),
+            from_surface: Some(true),
+            // Long receipts run far below the window
+            capture_beyond_viewport: Some(true),
+            optimize_for_speed: None,
+        })
+        .map_err(|e| PrintError::browser("screenshot", e))?
+        .data;
+
+    let png = STANDARD.decode(png).map_err(|e| PrintError::browser("screenshot", e))?;
+    let image = fit_width(decode_png(&png)?, request.width.dots());
+    Ok(escpos::encode(&escpos::dither(&image), request.cut, request.open_cash_drawer))
+}
+
+/// Writes the job to the device file or raw socket
+pub fn send_receipt(destination: &ReceiptDestination, job: &[u8]) -> Result<(), PrintError> {
+    match destination {
+        ReceiptDestination::Device { path } => {
+            let printer = path.display().to_string();
+            // Devices must exist already; never create a regular file in their place
+            let mut device = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(|e| PrintError::PrinterUnavailable { printer: printer.clone(), reason: e.to_string() })?;
+            device
+                .write_all(job)
+                .and_then(|_| device.flush())
+                .map_err(|e| PrintError::PrinterUnavailable { printer, reason: e.to_string() })
+        }
+        ReceiptDestination::Socket { host, port } => {
+            let socket = RawSocketPrinter { host: host.clone(), port: *port, timeout: SOCKET_TIMEOUT };
+            socket.send(&destination.to_string(), job)
+        }
+    }
+}
+
+/// Wraps the content in a column exactly as wide as the printable area
+pub fn build_receipt_html(content: &str, width: ReceiptWidth) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Receipt</title>
+    <style>
+        * {{
+            margin: 0;
+            padding: 0;
+            box-sizing: border-box;
+        }}
+
+        body {{
+            background: white;
+        }}
+
+        .receipt {{
+            width: {}mm;
+            background: white;
+            color: black;
+            font-family: Arial, Helvetica, sans-serif;
+            font-size: 10pt;
+            line-height: 1.3;
+            overflow-wrap: break-word;
+        }}
+
+        .receipt img {{
+            max-width: 100%;
+        }}
+    </style>
+</head>
+<body>
+<div class="receipt">{}</div>
+</body>
+</html>"#,
+        width.printable_mm(),
+        content
+    )
+}
+
+/// Decodes a PNG screenshot to grayscale, flattening transparency onto white
+fn decode_png(data: &[u8]) -> Result<GrayImage, PrintError> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| PrintError::browser("screenshot", e))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| PrintError::browser("screenshot", e))?;
+
+    let channels = info.color_type.samples();
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| {
+            let (luma, alpha) = match *pixel {
+                [gray] => (gray as f32, 255.0),
+                [gray, alpha] => (gray as f32, alpha as f32),
+                [r, g, b] => (luma(r, g, b), 255.0),
+                [r, g, b, alpha] => (luma(r, g, b), alpha as f32),
+                _ => (255.0, 255.0),
+            };
+            (luma * alpha / 255.0 + 255.0 - alpha).round() as u8
+        })
+        .collect();
+
+    Ok(GrayImage { width: info.width, height: info.height, pixels })
+}
+
+/// Crops or pads (with white) the image to exactly `dots` columns
+///
+/// Chrome rounds the scaled screenshot, so it can come out a dot narrower or wider
+/// than the print head, which would shift every raster row.
+fn fit_width(image: GrayImage, dots: u32) -> GrayImage {
+    if image.width == dots {
+        return image;
+    }
+
+    let (from, to) = (image.width as usize, dots as usize);
+    let mut pixels = Vec::with_capacity(to * image.height as usize);
+    for row in image.pixels.chunks_exact(from) {
+        pixels.extend_from_slice(&row[..from.min(to)]);
+        pixels.resize(pixels.len() + to.saturating_sub(from), 255);
+    }
+    GrayImage { width: dots, height: image.height, pixels }
+}
+
+/// ITU-R BT.601 luma
+fn luma(r: u8, g: u8, b: u8) -> f32 {
+    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::receipt::ReceiptCut;
+
+    fn encode_png(width: u32, height: u32, color_type: png::ColorType, data: &[u8]) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(data).unwrap();
+        png
+    }
+
+    #[test]
+    fn decodes_rgba_screenshots_onto_white() {
+        let png = encode_png(3, 1, png::ColorType::Rgba, &[0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 0]);
+
+        let image = decode_png(&png).unwrap();
+
+        assert_eq!((image.width, image.height), (3, 1));
+        assert_eq!(image.pixels, vec![0, 255, 255]);
+    }
+
+    #[test]
+    fn screenshots_are_fitted_to_the_print_head() {
+        let raster_bytes_per_row = |screenshot_width: u32, width: ReceiptWidth| {
+            let pixels = vec![0; screenshot_width as usize * 2];
+            let image = fit_width(GrayImage { width: screenshot_width, height: 2, pixels }, width.dots());
+            let job = escpos::encode(&escpos::dither(&image), ReceiptCut::None, false);
+            let header = job.windows(4).position(|bytes| bytes == [0x1D, 0x76, 0x30, 0x00]).unwrap();
+            u16::from_le_bytes([job[header + 4], job[header + 5]])
+        };
+
+        assert_eq!(raster_bytes_per_row(383, ReceiptWidth::Mm58), 48);
+        assert_eq!(raster_bytes_per_row(385, ReceiptWidth::Mm58), 48);
+        assert_eq!(raster_bytes_per_row(575, ReceiptWidth::Mm80), 72);
+        assert_eq!(raster_bytes_per_row(577, ReceiptWidth::Mm80), 72);
+    }
+
+    #[test]
+    fn padding_is_white_and_cropping_keeps_the_left_columns() {
+        let image = GrayImage { width: 2, height: 2, pixels: vec![0, 10, 20, 30] };
+
+        assert_eq!(fit_width(image.clone(), 3).pixels, vec![0, 10, 255, 20, 30, 255]);
+        assert_eq!(fit_width(image, 1).pixels, vec![0, 20]);
+    }
+
+    #[test]
+    fn html_column_matches_the_printable_width() {
+        let html = build_receipt_html("<p>Total 4.20</p>", ReceiptWidth::Mm58);
+
+        assert!(html.contains("width: 48mm;"));
+        assert!(html.contains(r#"<div class="receipt"><p>Total 4.20</p></div>"#));
+    }
+
+    #[test]
+    fn missing_device_is_unavailable() {
+        let destination = ReceiptDestination::Device { path: std::env::temp_dir().join("no-such-dir/lp0") };
+
+        let error = send_receipt(&destination, b"\x1b@").unwrap_err();
+
+        assert_eq!(error.code(), "printer_unavailable");
+    }
+}
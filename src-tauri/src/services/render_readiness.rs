@@ -0,0 +1,76 @@
+use std::time::Duration;
+use headless_chrome::Tab;
+use serde_json::Value;
+use crate::models::print_config::PrintConfig;
+use crate::models::print_error::PrintError;
+
+/// Extra time given to the DevTools call on top of the in-page timeout,
+/// so the page reports which resource is late instead of the call failing
+const DEVTOOLS_TIMEOUT_SLACK: Duration = Duration::from_secs(5);
+
+/// Waits until the loaded document is ready to be printed
+///
+/// Readiness means `document.fonts.ready` resolved, every `<img>` finished loading
+/// (or failed), and, when `wait_for_print_ready` is set, the page set
+/// `window.__printReady = true`. Returns an error naming the pending resources
+/// if this does not happen within `render_timeout_ms`.
+pub fn wait_until_render_ready(tab: &Tab, config: &PrintConfig) -> Result<(), PrintError> {
+    wait_until_ready(tab, config.render_timeout_ms, config.wait_for_print_ready)
+}
+
+/// Same as [`wait_until_render_ready`] for content loaded without a [`PrintConfig`]
+pub fn wait_until_ready(tab: &Tab, render_timeout_ms: u64, wait_for_print_ready: bool) -> Result<(), PrintError> {
+    let timeout = Duration::from_millis(render_timeout_ms);
+    tab.set_default_timeout(timeout + DEVTOOLS_TIMEOUT_SLACK);
+
+    let script = readiness_script(render_timeout_ms, wait_for_print_ready);
+    let result = tab.evaluate(&script, true)
+        .map_err(|e| PrintError::browser("render_ready", e))?;
+
+    match result.value {
+        Some(Value::String(status)) if status == "ready" => Ok(()),
+        Some(Value::String(status)) => {
+            let pending = status.strip_prefix("timeout:").unwrap_or(&status);
+            Err(PrintError::RenderTimeout {
+                timeout_ms: render_timeout_ms,
+                pending: pending.split(", ").map(str::to_string).collect(),
+            })
+        }
+        other => Err(PrintError::browser("render_ready", format!("unexpected result {:?}", other))),
+    }
+}
+
+/// Builds the script that resolves to "ready" or "timeout:<pending stages>"
+fn readiness_script(timeout_ms: u64, wait_for_print_ready: bool) -> String {
+    format!(
+        r#"(() => {{
+    const pending = new Set(['fonts', 'images']);
+    const done = (stage) => () => pending.delete(stage);
+
+    const fonts = (document.fonts ? document.fonts.ready : Promise.resolve())
+        .then(done('fonts'));
+
+    const images = Promise.all(Array.from(document.images).map((img) =>
+        img.complete ? Promise.resolve() : new Promise((resolve) => {{
+            img.addEventListener('load', resolve, {{ once: true }});
+            img.addEventListener('error', resolve, {{ once: true }});
+        }})
+    )).then(done('images'));
+
+    const stages = [fonts, images];
+    if ({wait_for_print_ready}) {{
+        pending.add('window.__printReady');
+        stages.push(new Promise((resolve) => {{
+            const check = () => window.__printReady === true ? resolve() : setTimeout(check, 25);
+            check();
+        }}).then(done('window.__printReady')));
+    }}
+
+    const ready = Promise.all(stages).then(() => 'ready');
+    const timeout = new Promise((resolve) =>
+        setTimeout(() => resolve('timeout:' + Array.from(pending).join(', ')), {timeout_ms})
+    );
+    return Promise.race([ready, timeout]);
+}})()"#
+    )
+}
@@ -0,0 +1,55 @@
+//! TCP helpers shared by the network printing protocols (IPP, raw socket, LPD)
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Splits `host[:port]` or `[v6]:port`, dropping any `user@`
+pub fn split_host_port(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    // Credentials are never sent, so drop any `user@`
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+    Some((host.to_string(), port))
+}
+
+/// Connects to the first reachable address of `host`, with read and write timeouts set
+///
+/// The error names the host and port, for the caller to classify.
+pub fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream, String> {
+    let addresses = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("{}:{}: {}", host, port, e))?;
+
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout)).ok();
+                stream.set_write_timeout(Some(timeout)).ok();
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(match last_error {
+        Some(e) => format!("{}:{}: {}", host, port, e),
+        None => format!("{}:{}: no address", host, port),
+    })
+}
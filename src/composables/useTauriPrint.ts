@@ -1,27 +1,55 @@
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { save } from '@tauri-apps/plugin-dialog';
+import { isPrintError } from '../types/print';
 import type { 
   TauriPrintRequestPages, 
   PrintSettings,
   PageData,
+  PdfExportOptions,
+  ConformanceReport,
+  SavedPdf,
+  PrintError,
+  PrinterCapabilities,
+  PrinterInfo,
+  JobFilter,
+  JobStatus,
+  PrintProgress,
+  PrintResult,
+  TauriReceiptRequest,
   PAPER_DIMENSIONS 
 } from '../types/print';
 
 export function useTauriPrint() {
   const isLoading = ref(false);
   const error = ref<string | null>(null);
+  const errorCode = ref<PrintError['code'] | null>(null);
+  // Latest stage of the running save, null when idle
+  const progress = ref<PrintProgress | null>(null);
+  let runningTaskId: string | null = null;
 
   /**
-   * Save document as PDF (each page treated individually, merged in backend)
+   * Save document as PDF (each page treated individually, merged in backend),
+   * encrypted, signed or converted to PDF/A as the export options ask
    */
   const saveAsPDF = async (
     pages: PageData[],
     settings: PrintSettings,
-    _paperDimensions: typeof PAPER_DIMENSIONS
-  ): Promise<{ success: boolean; message: string; path?: string }> => {
+    _paperDimensions: typeof PAPER_DIMENSIONS,
+    exportOptions?: PdfExportOptions
+  ): Promise<{ success: boolean; message: string; path?: string; conformance?: ConformanceReport }> => {
     isLoading.value = true;
     error.value = null;
+    errorCode.value = null;
+
+    const taskId = crypto.randomUUID();
+    runningTaskId = taskId;
+    const unlisten = await listen<PrintProgress>('print-progress', (event) => {
+      if (event.payload.task_id === taskId) {
+        progress.value = event.payload;
+      }
+    });
 
     try {
       // Open save dialog FIRST
@@ -53,32 +81,96 @@ export function useTauriPrint() {
           margins_mm: settings.margins,
           scale: 1.0
         },
-        pages: pagesContent
+        pages: pagesContent,
+        task_id: taskId
       };
 
       // Save PDF with merge in backend
-      const finalPath = await invoke<string>('save_pdf_pages_to_path', { 
+      const saved = await invoke<SavedPdf>('save_pdf_pages_to_path', { 
         request,
-        destinationPath: savePath 
+        destinationPath: savePath,
+        exportOptions
       });
 
       return {
         success: true,
         message: 'PDF saved successfully',
-        path: finalPath
+        path: saved.path,
+        conformance: saved.conformance
       };
     } catch (e) {
-      const errorMsg = e instanceof Error ? e.message : String(e);
+      if (isPrintError(e) && e.code === 'print_canceled') {
+        return {
+          success: false,
+          message: 'Operation cancelled by user'
+        };
+      }
+      const errorMsg = isPrintError(e) ? e.message : e instanceof Error ? e.message : String(e);
       error.value = errorMsg;
+      errorCode.value = isPrintError(e) ? e.code : null;
       throw new Error(errorMsg);
     } finally {
+      unlisten();
+      runningTaskId = null;
+      progress.value = null;
       isLoading.value = false;
     }
   };
 
+  /**
+   * Media, orientations, duplex and color modes the printer supports
+   */
+  const getPrinters = async (): Promise<PrinterInfo[]> => {
+    return invoke<PrinterInfo[]>('get_printers');
+  };
+
+  const getPrinterCapabilities = async (printerId: string): Promise<PrinterCapabilities> => {
+    return invoke<PrinterCapabilities>('get_printer_capabilities', { printerId });
+  };
+
+  /**
+   * Stop the running save before its next stage
+   */
+  const cancelPrint = async (): Promise<void> => {
+    if (runningTaskId) {
+      await invoke<void>('cancel_print', { taskId: runningTaskId });
+    }
+  };
+
+  const getJobStatus = async (printerId: string, jobId: string): Promise<JobStatus> => {
+    return invoke<JobStatus>('get_job_status', { printerId, jobId });
+  };
+
+  const cancelJob = async (printerId: string, jobId: string): Promise<void> => {
+    return invoke<void>('cancel_job', { printerId, jobId });
+  };
+
+  /**
+   * List jobs of one printer, or of every printer when printerId is omitted
+   */
+  const listJobs = async (printerId?: string, filter: JobFilter = 'active'): Promise<JobStatus[]> => {
+    return invoke<JobStatus[]>('list_jobs', { printerId, filter });
+  };
+
+  /**
+   * Render HTML for a 58/80 mm thermal printer and send it as ESC/POS
+   */
+  const printReceipt = async (request: TauriReceiptRequest): Promise<PrintResult> => {
+    return invoke<PrintResult>('print_receipt', { request });
+  };
+
   return {
     isLoading,
     error,
-    saveAsPDF
+    errorCode,
+    progress,
+    saveAsPDF,
+    cancelPrint,
+    getPrinters,
+    getPrinterCapabilities,
+    getJobStatus,
+    cancelJob,
+    listJobs,
+    printReceipt
   };
 }
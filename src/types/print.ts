@@ -1,5 +1,9 @@
 export type Orientation = 'portrait' | 'landscape';
-export type PaperFormat = 'A4' | 'A3' | 'Letter';
+export type PaperFormat = 'A4' | 'A3' | 'Letter' | 'Legal';
+
+// Backend also accepts any ISO A/B/C name, Tabloid, Ledger, Executive, DL, Envelope10
+// or explicit dimensions (e.g. receipts and labels)
+export type TauriPaperFormat = PaperFormat | string | { width_mm: number; height_mm: number };
 
 export interface PageData {
   id: string;
@@ -15,38 +19,294 @@ export interface PrintSettings {
 export const PAPER_DIMENSIONS: Record<PaperFormat, { width: number; height: number }> = {
   A4: { width: 210, height: 297 },      // mm
   A3: { width: 297, height: 420 },      // mm
-  Letter: { width: 215.9, height: 279.4 } // mm
+  Letter: { width: 215.9, height: 279.4 }, // mm
+  Legal: { width: 215.9, height: 355.6 }   // mm
 };
 
 
+export type PrinterState = 'idle' | 'printing' | 'stopped';
+
 export interface PrinterInfo {
   id: string;
-  display_name: string;
+  display_name: string; // printer-info description, or the id
   is_default: boolean;
+  state: PrinterState;
+  state_reasons: string[]; // IPP keywords, e.g. "media-empty-error"
+  accepting_jobs: boolean;
+  location: string | null;
+  make_and_model: string | null;
+  description: string | null;
+}
+
+export type Sides = 'one_sided' | 'two_sided_long_edge' | 'two_sided_short_edge';
+export type ColorMode = 'color' | 'monochrome';
+
+export interface MediaSize {
+  name: string; // PWG media name, e.g. "iso_a4_210x297mm"
+  width_mm: number;
+  height_mm: number;
+  format: TauriPaperFormat | null;
+}
+
+export interface PrinterCapabilities {
+  media: MediaSize[];
+  default_media: string | null;
+  paper_formats: TauriPaperFormat[];
+  custom_sizes: boolean;
+  orientations: Orientation[];
+  sides: Sides[];
+  color_modes: ColorMode[];
+  resolutions: { x_dpi: number; y_dpi: number }[];
+  max_copies: number | null;
+  page_ranges: boolean;
+  collate: boolean;
+  scaling_modes: ScalingMode[];
+  input_trays: string[];
+  hardware_margins_mm: { top: number; right: number; bottom: number; left: number };
+}
+
+export interface TauriMargins {
+  top: number;
+  right: number;
+  bottom: number;
+  left: number;
+  inside?: number;  // duplex binding side, mirrored with outside
+  outside?: number;
 }
 
 export interface TauriPrintConfig {
-  format: PaperFormat;
+  format: TauriPaperFormat;
   orientation: Orientation;
   margins_mm: number;
+  margins?: TauriMargins; // takes precedence over margins_mm
   scale: number;
+  render_timeout_ms?: number;
+  wait_for_print_ready?: boolean;
+  // Running header/footer HTML with {{page}}, {{pages}}, {{date}} and {{title}} placeholders
+  header_template?: string;
+  footer_template?: string;
+  document_title?: string;
+  fail_on_overflow?: boolean; // refuse to print while content is cut off
+}
+
+export type ScalingMode = 'fit' | 'none' | 'fill';
+
+// Job options; omitted fields keep the printer defaults
+export interface TauriJobOptions {
+  copies?: number;
+  collate?: boolean;
+  sides?: Sides;
+  color_mode?: ColorMode;
+  page_ranges?: { from: number; to: number }[]; // 1-based, inclusive, ascending
+  scaling?: ScalingMode; // default 'fit'
 }
 
 export interface TauriPrintRequest {
   config: TauriPrintConfig;
   html_content: string;
   printer_id?: string; 
+  options?: TauriJobOptions;
+  task_id?: string; // carried by print-progress events, accepted by cancel_print
+}
+
+// Written into the PDF Info dictionary and XMP metadata
+export interface PdfMetadata {
+  title?: string;      // also the HTML <title> while rendering
+  author?: string;
+  subject?: string;
+  keywords?: string[];
+  language?: string;   // BCP 47 tag, also <html lang>
+  created_at?: number; // Unix seconds, default: time of export
+  modified_at?: number; // Unix seconds, default: created_at
+}
+
+// PDF outline source: h1–h6 headings, or elements with data-bookmark / data-bookmark-level
+export type OutlineSource = 'headings' | 'bookmarks';
+
+// AES-256 encryption of saved PDFs
+export interface PdfProtection {
+  user_password?: string;  // needed to open the PDF
+  owner_password?: string; // lifts the restrictions, random when omitted
+  no_print?: boolean;
+  no_copy?: boolean;
+  no_edit?: boolean;
+}
+
+// PAdES signature of saved PDFs (RSA key)
+export interface PdfSignature {
+  pkcs12_path: string; // .p12/.pfx file with the private key and certificate chain
+  password: string;
+  reason?: string;
+  location?: string;
+  contact_info?: string;
+  appearance?: SignatureAppearance; // invisible signature when omitted
+}
+
+// Visible signature box, measured from the top-left corner of the page
+export interface SignatureAppearance {
+  page_index: number;
+  x_mm: number;
+  y_mm: number;
+  width_mm: number;
+  height_mm: number;
+}
+
+export type PdfConformance = 'pdf_a_2b';
+
+// Options of saved PDFs only, passed next to the request to the save commands
+export interface PdfExportOptions {
+  protection?: PdfProtection;
+  signature?: PdfSignature;  // not together with protection
+  conformance?: PdfConformance; // no protection, invisible signature only
+}
+
+// What the PDF/A conversion changed and what keeps the file from conforming
+export interface ConformanceReport {
+  conformance: PdfConformance;
+  conformant: boolean; // no blockers, the XMP claims PDF/A
+  changes: string[];
+  blockers: string[];
+}
+
+// Result of save_pdf_to_path / save_pdf_pages_to_path
+export interface SavedPdf {
+  path: string;
+  conformance?: ConformanceReport; // only when PDF/A was asked for
 }
 
 export interface TauriPrintRequestPages {
   config: TauriPrintConfig;
   pages: string[]; 
   printer_id?: string; 
+  options?: TauriJobOptions;
+  metadata?: PdfMetadata;
+  outline?: OutlineSource;
+  task_id?: string; // carried by print-progress events, accepted by cancel_print
+}
+
+export type PrintErrorCode =
+  | 'chrome_not_found'
+  | 'browser_failed'
+  | 'render_timeout'
+  | 'content_overflow'
+  | 'pdf_processing_failed'
+  | 'invalid_request'
+  | 'print_canceled'
+  | 'queue_full'
+  | 'internal_error'
+  | 'no_default_printer'
+  | 'printer_not_found'
+  | 'printer_unavailable'
+  | 'spooler_unavailable'
+  | 'option_not_supported'
+  | 'job_rejected'
+  | 'job_not_found'
+  | 'disk_full'
+  | 'permission_denied'
+  | 'io_error';
+
+// Error returned by every print command
+export interface PrintError {
+  code: PrintErrorCode;
+  message: string;
+  details: Record<string, unknown> | null;
+}
+
+export function isPrintError(e: unknown): e is PrintError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+}
+
+export interface PageOverflow {
+  page_index: number;
+  overflow_mm: number;
+}
+
+export interface OverflowReport {
+  pages: PageOverflow[]; // only overflowing pages
+}
+
+export interface TauriPaginateRequest {
+  config: TauriPrintConfig;
+  html_content: string; // long document to flow across pages
+}
+
+export interface PaginatedDocument {
+  page_count: number;
+  pages: string[]; // HTML of each page, same split as the PDF
+}
+
+export interface TauriPreviewRequest {
+  config: TauriPrintConfig;
+  pages: string[];
+  dpi?: number;        // default 96
+  page_index?: number; // render a single page for fast refresh
+}
+
+export interface PagePreview {
+  page_index: number;
+  width_px: number;
+  height_px: number;
+  png_base64: string;
 }
 
+export type JobState =
+  | 'pending'
+  | 'held'
+  | 'processing'
+  | 'stopped'
+  | 'canceled'
+  | 'aborted'
+  | 'completed';
+
+export interface JobStatus {
+  job_id: string;
+  printer: string;
+  name: string | null;
+  state: JobState;
+  state_reasons: string[];
+  created_at: number | null;   // Unix seconds
+  completed_at: number | null; // Unix seconds
+}
+
+export type JobFilter = 'active' | 'finished' | 'all';
+
 export interface PrintResult {
   success: boolean;
   message: string;
   job_id?: string;
+  printer?: string; // resolved printer, also when the default was used
+  job_state?: JobState;
+  overflow?: PageOverflow[];
+  error?: PrintError;
 }
 
+// Thermal receipt printed as ESC/POS (print_receipt)
+export type ReceiptWidth = '58mm' | '80mm';
+
+export type ReceiptCut = 'none' | 'partial' | 'full';
+
+export type ReceiptDestination =
+  | { kind: 'device'; path: string }             // e.g. /dev/usb/lp0 or \\.\COM3
+  | { kind: 'socket'; host: string; port?: number }; // default port 9100
+
+export interface TauriReceiptRequest {
+  html_content: string;
+  width: ReceiptWidth;
+  destination: ReceiptDestination;
+  cut?: ReceiptCut;            // default 'partial'
+  open_cash_drawer?: boolean;  // default false
+  render_timeout_ms?: number;  // default 10000
+  task_id?: string;
+}
+
+// Payload of the 'print-progress' event
+export type PrintStage =
+  | { stage: 'queued' }
+  | { stage: 'starting_browser' }
+  | { stage: 'loading_html' }
+  | { stage: 'rendering'; page: number; pages: number } // once per batch of pages, `page` is 1-based
+  | { stage: 'writing_pdf' }
+  | { stage: 'submitting'; printer: string }
+  | { stage: 'job_state'; job_id: string; state: JobState };
+
+export type PrintProgress = PrintStage & { task_id: string };